@@ -1,3 +1,4 @@
+use advent_of_code::{day10, day11, gen};
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 use std::collections::HashMap;
 
@@ -69,5 +70,32 @@ fn criterion_benchmark(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, criterion_benchmark);
+fn day10_sizes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day10.2");
+    for &n in [25, 50, 100, 200, 255].iter() {
+        let input = day10::input_generator(&gen::adapters(&mut gen::rng(10), n)).unwrap();
+        group.throughput(criterion::Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &input, |b, input| {
+            b.iter(|| day10::part2(black_box(input)))
+        });
+    }
+}
+
+fn day11_sizes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day11");
+    for &side in [10, 20, 40, 80].iter() {
+        let input =
+            day11::input_generator(&gen::settling_seat_map(&mut gen::rng(11), side, side, 0.2))
+                .unwrap();
+        group.throughput(criterion::Throughput::Elements((side * side) as u64));
+        group.bench_with_input(BenchmarkId::new("part1", side), &input, |b, input| {
+            b.iter(|| day11::part1(black_box(input)))
+        });
+        group.bench_with_input(BenchmarkId::new("part2", side), &input, |b, input| {
+            b.iter(|| day11::part2(black_box(input)).unwrap())
+        });
+    }
+}
+
+criterion_group!(benches, criterion_benchmark, day10_sizes, day11_sizes);
 criterion_main!(benches);
@@ -0,0 +1,192 @@
+use std::{
+    collections::HashSet,
+    fmt::{self, Display},
+};
+
+#[derive(Debug, PartialEq)]
+pub struct NoSolution {
+    pub k: usize,
+    pub target: i64,
+}
+
+impl Display for NoSolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no {} entries sum to {}", self.k, self.target)
+    }
+}
+
+impl std::error::Error for NoSolution {}
+
+/// Every distinct `k`-tuple of entries summing to `target`, each sorted ascending.
+///
+/// An entry is used at most once per tuple, so `1010 + 1010` needs two entries of `1010`.
+pub fn k_sum(entries: &[i64], k: usize, target: i64) -> Result<Vec<Vec<i64>>, NoSolution> {
+    let mut tuples = if k == 2 {
+        two_sum(entries, target)
+    } else {
+        let mut sorted = entries.to_vec();
+        sorted.sort_unstable();
+        let mut tuples = Vec::new();
+        k_sum_sorted(&sorted, k, target, &mut Vec::with_capacity(k), &mut tuples);
+        tuples
+    };
+    if tuples.is_empty() {
+        return Err(NoSolution { k, target });
+    }
+    tuples.sort_unstable();
+    tuples.dedup();
+    Ok(tuples)
+}
+
+fn two_sum(entries: &[i64], target: i64) -> Vec<Vec<i64>> {
+    let mut seen = HashSet::with_capacity(entries.len());
+    let mut pairs = Vec::new();
+    for &entry in entries {
+        let other = target - entry;
+        if seen.contains(&other) {
+            pairs.push(vec![other.min(entry), other.max(entry)]);
+        }
+        seen.insert(entry);
+    }
+    pairs
+}
+
+/// Fixes the smallest remaining entry and recurses, finishing with two pointers for the last pair.
+fn k_sum_sorted(
+    sorted: &[i64],
+    k: usize,
+    target: i64,
+    prefix: &mut Vec<i64>,
+    tuples: &mut Vec<Vec<i64>>,
+) {
+    match k {
+        0 => {
+            if target == 0 {
+                tuples.push(prefix.clone());
+            }
+        }
+        1 => {
+            if sorted.binary_search(&target).is_ok() {
+                let mut tuple = prefix.clone();
+                tuple.push(target);
+                tuples.push(tuple);
+            }
+        }
+        2 => {
+            let (mut lo, mut hi) = (0, sorted.len());
+            while lo + 1 < hi {
+                let sum = sorted[lo] + sorted[hi - 1];
+                match sum.cmp(&target) {
+                    std::cmp::Ordering::Less => lo += 1,
+                    std::cmp::Ordering::Greater => hi -= 1,
+                    std::cmp::Ordering::Equal => {
+                        let mut tuple = prefix.clone();
+                        tuple.extend(&[sorted[lo], sorted[hi - 1]]);
+                        tuples.push(tuple);
+                        let (low, high) = (sorted[lo], sorted[hi - 1]);
+                        while lo < hi && sorted[lo] == low {
+                            lo += 1;
+                        }
+                        while lo < hi && sorted[hi - 1] == high {
+                            hi -= 1;
+                        }
+                    }
+                }
+            }
+        }
+        _ => {
+            for i in 0..sorted.len() {
+                if i > 0 && sorted[i] == sorted[i - 1] {
+                    continue;
+                }
+                prefix.push(sorted[i]);
+                k_sum_sorted(&sorted[i + 1..], k - 1, target - sorted[i], prefix, tuples);
+                prefix.pop();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use crate::{k_sum, NoSolution};
+
+    fn read(file: &str) -> Vec<i64> {
+        read_to_string(file)
+            .unwrap()
+            .lines()
+            .map(|l| l.parse().unwrap())
+            .collect()
+    }
+
+    fn input() -> Vec<i64> {
+        read("input.txt")
+    }
+
+    #[test]
+    fn part1() {
+        let tuples = k_sum(&input(), 2, 2020).unwrap();
+        assert_eq!(1, tuples.len());
+        assert_eq!(2020, tuples[0].iter().sum::<i64>());
+        assert_eq!(618144, tuples[0].iter().product::<i64>());
+    }
+
+    #[test]
+    fn part2() {
+        let tuples = k_sum(&input(), 3, 2020).unwrap();
+        assert_eq!(1, tuples.len());
+        assert_eq!(173538720, tuples[0].iter().product::<i64>());
+    }
+
+    #[test]
+    fn duplicates_and_edge_cases() {
+        assert_eq!(Ok(vec![vec![1010, 1010]]), k_sum(&[1010, 1010], 2, 2020));
+        assert_eq!(
+            Err(NoSolution { k: 2, target: 2020 }),
+            k_sum(&[1010], 2, 2020)
+        );
+        assert_eq!(Ok(vec![vec![1, 1, 2]]), k_sum(&[2, 1, 1, 1], 3, 4));
+        assert_eq!(
+            Ok(vec![vec![-2, 0, 2], vec![-1, -1, 2], vec![-1, 0, 1]]),
+            k_sum(&[-1, 0, 1, 2, -1, -2], 3, 0)
+        );
+        let example = read("../../examples/2020/day01/example.txt");
+        assert_eq!(Ok(vec![vec![979]]), k_sum(&example, 1, 979));
+        assert_eq!(Ok(vec![vec![]]), k_sum(&example, 0, 0));
+        assert_eq!(
+            Err(NoSolution { k: 4, target: 2020 }),
+            k_sum(&example, 4, 2020)
+        );
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let entries = [3, 9, -4, 7, 0, 3, 12, -8, 5, 1];
+        for k in 2..=4 {
+            for target in -10..=25 {
+                let mut expected = Vec::new();
+                let n = entries.len();
+                for mask in 0u32..(1 << n) {
+                    if mask.count_ones() as usize == k {
+                        let mut tuple: Vec<i64> = (0..n)
+                            .filter(|i| mask & (1 << i) != 0)
+                            .map(|i| entries[i])
+                            .collect();
+                        if tuple.iter().sum::<i64>() == target {
+                            tuple.sort_unstable();
+                            expected.push(tuple);
+                        }
+                    }
+                }
+                expected.sort_unstable();
+                expected.dedup();
+                match k_sum(&entries, k, target) {
+                    Ok(tuples) => assert_eq!(expected, tuples, "k={} target={}", k, target),
+                    Err(_) => assert!(expected.is_empty(), "k={} target={}", k, target),
+                }
+            }
+        }
+    }
+}
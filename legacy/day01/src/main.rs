@@ -1,43 +1,22 @@
-use std::{
-    fs::File,
-    io::{self, BufRead},
-    path::Path,
-};
+use std::fs::read_to_string;
 
-pub fn main2() {
-    let lines = read_lines("input.txt").unwrap();
-    let mut old_numbers = Vec::new();
-    for line in lines {
-        let number = line.unwrap().parse::<i32>().unwrap();
-        for old in old_numbers.iter() {
-            if number + old == 2020 {
-                println!("{}x{}={}", number, old, number * old);
-            }
-        }
-        old_numbers.insert(0, number);
-    }
-}
+use day01::k_sum;
 
-pub fn main() {
-    let lines = read_lines("input.txt").unwrap();
-    let mut old_numbers = Vec::new();
-    for line in lines {
-        let number = line.unwrap().parse::<i32>().unwrap();
-        for old2 in old_numbers.iter() {
-            for old in old_numbers.iter() {
-                if number + old + old2 == 2020 {
-                    println!("{}x{}z{}={}", number, old, old2, number * old * old2);
+fn main() {
+    let entries = read_to_string("input.txt")
+        .unwrap()
+        .lines()
+        .map(|l| l.parse::<i64>().unwrap())
+        .collect::<Vec<_>>();
+    for k in 2..=3 {
+        match k_sum(&entries, k, 2020) {
+            Ok(tuples) => {
+                for tuple in tuples {
+                    let factors = tuple.iter().map(i64::to_string).collect::<Vec<_>>();
+                    println!("{}={}", factors.join("x"), tuple.iter().product::<i64>());
                 }
             }
+            Err(e) => println!("{}", e),
         }
-        old_numbers.insert(0, number);
     }
 }
-
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where
-    P: AsRef<Path>,
-{
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
-}
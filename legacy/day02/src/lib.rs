@@ -0,0 +1,266 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+/// One line of the password database: `1-3 a: abcde`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PolicyEntry {
+    pub first: usize,
+    pub second: usize,
+    pub letter: char,
+    pub password: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    MissingPart(&'static str),
+    BadNumber(String),
+    BadLetter(String),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingPart(part) => write!(f, "missing {}", part),
+            ParseError::BadNumber(n) => write!(f, "'{}' is not a number", n),
+            ParseError::BadLetter(l) => write!(f, "'{}' is not a single letter", l),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl FromStr for PolicyEntry {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn number(s: &str) -> Result<usize, ParseError> {
+            s.parse().map_err(|_| ParseError::BadNumber(s.to_string()))
+        }
+        let (policy, password) = s
+            .split_once(": ")
+            .ok_or(ParseError::MissingPart("': ' before the password"))?;
+        let (range, letter) = policy
+            .split_once(' ')
+            .ok_or(ParseError::MissingPart("letter"))?;
+        let (first, second) = range
+            .split_once('-')
+            .ok_or(ParseError::MissingPart("'-' between the numbers"))?;
+        let mut chars = letter.chars();
+        let letter = match (chars.next(), chars.next()) {
+            (Some(c), None) => c,
+            _ => return Err(ParseError::BadLetter(letter.to_string())),
+        };
+        Ok(PolicyEntry {
+            first: number(first)?,
+            second: number(second)?,
+            letter,
+            password: password.to_string(),
+        })
+    }
+}
+
+/// Parses the whole database, failing with the 1-based line number of the first bad line.
+pub fn parse_entries(input: &str) -> Result<Vec<PolicyEntry>, (usize, ParseError)> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, l)| l.parse().map_err(|e| (i + 1, e)))
+        .collect()
+}
+
+/// A rule deciding what the two numbers and the letter of an entry mean.
+pub trait Policy {
+    fn name(&self) -> &str;
+
+    /// `Err` explains why the password breaks the policy.
+    fn check(&self, entry: &PolicyEntry) -> Result<(), String>;
+}
+
+/// The letter has to appear between `first` and `second` times.
+pub struct CountInRange;
+
+impl Policy for CountInRange {
+    fn name(&self) -> &str {
+        "min-max"
+    }
+
+    fn check(&self, entry: &PolicyEntry) -> Result<(), String> {
+        let count = entry
+            .password
+            .chars()
+            .filter(|c| *c == entry.letter)
+            .count();
+        if (entry.first..=entry.second).contains(&count) {
+            Ok(())
+        } else {
+            Err(format!(
+                "'{}' appears {} times, expected {} to {}",
+                entry.letter, count, entry.first, entry.second
+            ))
+        }
+    }
+}
+
+/// The letter has to be at exactly one of the 1-based positions `first` and `second`.
+pub struct XorPosition;
+
+impl Policy for XorPosition {
+    fn name(&self) -> &str {
+        "pos-xor"
+    }
+
+    fn check(&self, entry: &PolicyEntry) -> Result<(), String> {
+        let at = |pos: usize| {
+            pos.checked_sub(1)
+                .and_then(|i| entry.password.chars().nth(i))
+                == Some(entry.letter)
+        };
+        match (at(entry.first), at(entry.second)) {
+            (true, false) | (false, true) => Ok(()),
+            (true, true) => Err(format!(
+                "'{}' is at both positions {} and {}",
+                entry.letter, entry.first, entry.second
+            )),
+            (false, false) => Err(format!(
+                "'{}' is at neither position {} nor {} of a {} letter password",
+                entry.letter,
+                entry.first,
+                entry.second,
+                entry.password.chars().count()
+            )),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Failure {
+    /// 0-based index into the checked entries.
+    pub entry: usize,
+    pub policy: String,
+    pub reason: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Report {
+    /// Number of valid entries per policy, in the order the policies were given.
+    pub valid: Vec<(String, usize)>,
+    pub failures: Vec<Failure>,
+}
+
+/// Checks every entry against every policy.
+pub fn report(entries: &[PolicyEntry], policies: &[&dyn Policy]) -> Report {
+    let mut valid = policies
+        .iter()
+        .map(|p| (p.name().to_string(), 0))
+        .collect::<Vec<_>>();
+    let mut failures = Vec::new();
+    for (i, entry) in entries.iter().enumerate() {
+        for (p, policy) in policies.iter().enumerate() {
+            match policy.check(entry) {
+                Ok(()) => valid[p].1 += 1,
+                Err(reason) => failures.push(Failure {
+                    entry: i,
+                    policy: policy.name().to_string(),
+                    reason,
+                }),
+            }
+        }
+    }
+    Report { valid, failures }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use crate::{
+        parse_entries, report, CountInRange, ParseError, Policy, PolicyEntry, XorPosition,
+    };
+
+    fn example() -> Vec<PolicyEntry> {
+        parse_entries(&read_to_string("../../examples/2020/day02/example.txt").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            Ok(PolicyEntry {
+                first: 1,
+                second: 3,
+                letter: 'a',
+                password: "abcde".to_string()
+            }),
+            "1-3 a: abcde".parse()
+        );
+        assert_eq!(
+            Err((2, ParseError::BadNumber("x".to_string()))),
+            parse_entries("1-3 a: abcde\n1-x a: abcde")
+        );
+        assert_eq!(
+            Err(ParseError::BadLetter("ab".to_string())),
+            "1-3 ab: abcde".parse::<PolicyEntry>()
+        );
+        assert!("1-3 a abcde".parse::<PolicyEntry>().is_err());
+    }
+
+    #[test]
+    fn failures() {
+        let entries = example();
+        let report = report(&entries, &[&CountInRange, &XorPosition]);
+        assert_eq!(
+            vec![("min-max".to_string(), 2), ("pos-xor".to_string(), 1)],
+            report.valid
+        );
+        assert_eq!(
+            vec![(1, "min-max"), (1, "pos-xor"), (2, "pos-xor")],
+            report
+                .failures
+                .iter()
+                .map(|f| (f.entry, f.policy.as_str()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn short_passwords() {
+        let entry: PolicyEntry = "4-9 a: abc".parse().unwrap();
+        assert!(XorPosition.check(&entry).is_err());
+        let entry: PolicyEntry = "1-9 a: abc".parse().unwrap();
+        assert!(XorPosition.check(&entry).is_ok());
+        let entry: PolicyEntry = "0-1 a: abc".parse().unwrap();
+        assert!(XorPosition.check(&entry).is_ok());
+    }
+
+    #[test]
+    fn custom_policy() {
+        struct NotAtStart;
+        impl Policy for NotAtStart {
+            fn name(&self) -> &str {
+                "not-at-start"
+            }
+            fn check(&self, entry: &PolicyEntry) -> Result<(), String> {
+                if entry.password.starts_with(entry.letter) {
+                    Err("starts with the letter".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+        }
+        let entries = example();
+        assert_eq!(
+            vec![("not-at-start".to_string(), 1)],
+            report(&entries, &[&NotAtStart]).valid
+        );
+    }
+
+    #[test]
+    fn input() {
+        let entries = parse_entries(&read_to_string("input.txt").unwrap()).unwrap();
+        assert_eq!(
+            vec![("min-max".to_string(), 640), ("pos-xor".to_string(), 472)],
+            report(&entries, &[&CountInRange, &XorPosition]).valid
+        );
+    }
+}
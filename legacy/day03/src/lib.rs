@@ -1,65 +1,212 @@
 use std::{
-    fs::File,
-    io::{self, BufRead},
-    path::Path,
+    fmt::{self, Display},
+    fs::read_to_string,
+    str::FromStr,
 };
 
-pub fn trees_on_slope(right: usize, down: usize, file: &str) -> usize {
-    let lines = read_lines(file).unwrap().step_by(down);
-    let mut trees = 0;
-    let mut pos = 0;
-    for line in lines {
-        let line = line.unwrap();
-        println!("{}", line);
-        println!("{}^", " ".repeat(pos));
-        if line.chars().nth(pos).unwrap() == '#' {
-            trees += 1;
+/// The tree map, parsed once. It repeats endlessly to the right.
+#[derive(Debug, PartialEq)]
+pub struct Map {
+    width: usize,
+    height: usize,
+    trees: Vec<bool>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    Empty,
+    /// 1-based line number, its width and the width of the first line.
+    Width(usize, usize, usize),
+    /// 1-based line and column.
+    UnknownChar(usize, usize, char),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "map is empty"),
+            ParseError::Width(line, width, expected) => {
+                write!(f, "line {} is {} wide, expected {}", line, width, expected)
+            }
+            ParseError::UnknownChar(line, col, c) => {
+                write!(f, "unknown char '{}' at {}:{}", c, line, col)
+            }
         }
-        pos += right;
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl FromStr for Map {
+    type Err = ParseError;
 
-        let max_index = line.len() - 1;
-        if pos > max_index {
-            pos -= line.len();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let width = s.lines().next().ok_or(ParseError::Empty)?.len();
+        if width == 0 {
+            return Err(ParseError::Empty);
         }
+        let mut trees = Vec::with_capacity(s.len());
+        let mut height = 0;
+        for (row, line) in s.lines().enumerate() {
+            if line.len() != width {
+                return Err(ParseError::Width(row + 1, line.len(), width));
+            }
+            for (col, c) in line.chars().enumerate() {
+                trees.push(match c {
+                    '#' => true,
+                    '.' => false,
+                    _ => return Err(ParseError::UnknownChar(row + 1, col + 1, c)),
+                });
+            }
+            height += 1;
+        }
+        Ok(Map {
+            width,
+            height,
+            trees,
+        })
     }
-    trees
 }
 
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where
-    P: AsRef<Path>,
-{
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+impl Map {
+    pub fn from_file(file: &str) -> Map {
+        read_to_string(file).unwrap().parse().unwrap()
+    }
+
+    fn is_tree(&self, row: usize, col: usize) -> bool {
+        self.trees[row * self.width + col % self.width]
+    }
+
+    /// Trees hit going `right` and `down` per step from the top left corner.
+    ///
+    /// `right` may be wider than the map. Panics if `down` is 0.
+    pub fn trees_on_slope(&self, right: usize, down: usize) -> usize {
+        (0..self.height)
+            .step_by(down)
+            .enumerate()
+            .filter(|(step, row)| self.is_tree(*row, (step * right) % self.width))
+            .count()
+    }
+
+    /// `trees_on_slope` for every `(right, down)` slope, walking the map only once.
+    ///
+    /// Panics if any `down` is 0.
+    pub fn trees_on_slopes(&self, slopes: &[(usize, usize)]) -> Vec<usize> {
+        assert!(
+            slopes.iter().all(|(_, down)| *down > 0),
+            "down must not be 0"
+        );
+        let mut trees = vec![0; slopes.len()];
+        for row in 0..self.height {
+            for (count, (right, down)) in trees.iter_mut().zip(slopes) {
+                if row % down == 0 && self.is_tree(row, (row / down * right) % self.width) {
+                    *count += 1;
+                }
+            }
+        }
+        trees
+    }
+
+    /// The slope with `right <= max_right` and `1 <= down <= max_down` hitting the fewest trees,
+    /// together with that count. Ties go to the smallest `down`, then the smallest `right`.
+    pub fn fewest_trees(
+        &self,
+        max_right: usize,
+        max_down: usize,
+    ) -> Option<((usize, usize), usize)> {
+        let slopes = (1..=max_down)
+            .flat_map(|down| (0..=max_right).map(move |right| (right, down)))
+            .collect::<Vec<_>>();
+        slopes
+            .iter()
+            .copied()
+            .zip(self.trees_on_slopes(&slopes))
+            .min_by_key(|(_, trees)| *trees)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::trees_on_slope;
+    use crate::{Map, ParseError};
+
+    const PART2_SLOPES: [(usize, usize); 5] = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
 
     #[test]
     fn part1_example() {
-        assert_eq!(trees_on_slope(3, 1, "example.txt"), 7);
+        assert_eq!(
+            Map::from_file("../../examples/2020/day03/example.txt").trees_on_slope(3, 1),
+            7
+        );
     }
     #[test]
     fn part1() {
-        assert_eq!(trees_on_slope(3, 1, "input.txt"), 223);
+        assert_eq!(Map::from_file("input.txt").trees_on_slope(3, 1), 223);
     }
     #[test]
     fn part2_examples() {
-        assert_eq!(trees_on_slope(1, 1, "example.txt"), 2);
-        assert_eq!(trees_on_slope(3, 1, "example.txt"), 7);
-        assert_eq!(trees_on_slope(5, 1, "example.txt"), 3);
-        assert_eq!(trees_on_slope(7, 1, "example.txt"), 4);
-        assert_eq!(trees_on_slope(1, 2, "example.txt"), 2);
+        let map = Map::from_file("../../examples/2020/day03/example.txt");
+        assert_eq!(map.trees_on_slope(1, 1), 2);
+        assert_eq!(map.trees_on_slope(3, 1), 7);
+        assert_eq!(map.trees_on_slope(5, 1), 3);
+        assert_eq!(map.trees_on_slope(7, 1), 4);
+        assert_eq!(map.trees_on_slope(1, 2), 2);
+        assert_eq!(vec![2, 7, 3, 4, 2], map.trees_on_slopes(&PART2_SLOPES));
     }
 
     #[test]
     fn part2() {
-        assert_eq!(trees_on_slope(1, 1, "input.txt"), 58);
-        assert_eq!(trees_on_slope(3, 1, "input.txt"), 223);
-        assert_eq!(trees_on_slope(5, 1, "input.txt"), 105);
-        assert_eq!(trees_on_slope(7, 1, "input.txt"), 74);
-        assert_eq!(trees_on_slope(1, 2, "input.txt"), 35);
+        let map = Map::from_file("input.txt");
+        assert_eq!(map.trees_on_slope(1, 1), 58);
+        assert_eq!(map.trees_on_slope(3, 1), 223);
+        assert_eq!(map.trees_on_slope(5, 1), 105);
+        assert_eq!(map.trees_on_slope(7, 1), 74);
+        assert_eq!(map.trees_on_slope(1, 2), 35);
+        assert_eq!(
+            vec![58, 223, 105, 74, 35],
+            map.trees_on_slopes(&PART2_SLOPES)
+        );
+    }
+
+    #[test]
+    fn wide_steps() {
+        let map = Map::from_file("../../examples/2020/day03/example.txt");
+        for &(right, down) in &PART2_SLOPES {
+            for wraps in 1..4 {
+                assert_eq!(
+                    map.trees_on_slope(right, down),
+                    map.trees_on_slope(right + wraps * 11, down)
+                );
+            }
+        }
+        assert_eq!(
+            map.trees_on_slopes(&[(3 + 110, 1), (1 + 11, 2)]),
+            vec![7, 2]
+        );
+    }
+
+    #[test]
+    fn fewest_trees() {
+        let map: Map = "..#\n#.#\n..#\n".parse().unwrap();
+        // every single-row slope hits a tree, straight down two rows at a time misses them all
+        assert_eq!(Some(((0, 2), 0)), map.fewest_trees(5, 2));
+        assert_eq!(Some(((0, 1), 1)), map.fewest_trees(5, 1));
+        let map = Map::from_file("../../examples/2020/day03/example.txt");
+        let ((right, down), trees) = map.fewest_trees(10, 3).unwrap();
+        assert_eq!(trees, map.trees_on_slope(right, down));
+        for down in 1..=3 {
+            for right in 0..=10 {
+                assert!(map.trees_on_slope(right, down) >= trees);
+            }
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Err(ParseError::Empty), "".parse::<Map>());
+        assert_eq!(Err(ParseError::Width(2, 2, 3)), "..#\n.#\n".parse::<Map>());
+        assert_eq!(
+            Err(ParseError::UnknownChar(2, 3, 'x')),
+            "..#\n.#x\n".parse::<Map>()
+        );
     }
 }
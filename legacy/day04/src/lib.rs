@@ -1,108 +1,294 @@
-use std::{collections::HashMap, fs::read_to_string};
+use records::records;
+use std::{collections::HashMap, fs::read_to_string, ops::RangeInclusive, str::FromStr};
 
-use regex::Regex;
-
-#[cfg(windows)]
-const LINE_ENDING: &str = "\r\n";
-#[cfg(not(windows))]
-const LINE_ENDING: &'static str = "\n";
+/// The rules from the puzzle, in the format read by `Schema::from_str`.
+pub const PUZZLE_SCHEMA: &str = "\
+byr required year 1920-2002
+iyr required year 2010-2020
+eyr required year 2020-2030
+hgt required units cm:150-193 in:59-76
+hcl required hex-colour
+ecl required one-of amb blu brn gry grn hzl oth
+pid required digits 9
+cid optional any
+";
 
 fn get_passports(file: &str) -> std::vec::Vec<String> {
-    read_to_string(file)
-        .unwrap()
-        .split(LINE_ENDING.repeat(2).as_str())
-        .map(|s| s.to_string())
+    records(&read_to_string(file).unwrap())
+        .map(|lines| lines.join("\n"))
         .collect::<Vec<_>>()
 }
 
-fn is_valid(passport: &str) -> bool {
-    let re = Regex::new(r"([a-z]+):(\S+)\s*").unwrap();
-    let props = re
-        .captures_iter(passport)
-        .map(|cap| (cap.get(1).unwrap().as_str(), cap.get(2).unwrap().as_str()))
-        .collect::<HashMap<&str, &str>>();
-
-    println!("{:?}", props);
-    props.get("byr").map(|v| valid_byr(v)).unwrap_or(false)
-        && props.get("iyr").map(|v| valid_iyr(v)).unwrap_or(false)
-        && props.get("eyr").map(|v| valid_eyr(v)).unwrap_or(false)
-        && props.get("hgt").map(|v| valid_hgt(v)).unwrap_or(false)
-        && props.get("hcl").map(|v| valid_hcl(v)).unwrap_or(false)
-        && props.get("ecl").map(|v| valid_ecl(v)).unwrap_or(false)
-        && props.get("pid").map(|v| valid_pid(v)).unwrap_or(false)
+/// Splits `key:value` pairs separated by any whitespace.
+fn parse_passport(passport: &str) -> HashMap<&str, &str> {
+    passport
+        .split_whitespace()
+        .map(|pair| pair.split_once(':').unwrap_or((pair, "")))
+        .collect()
 }
 
-fn valid_byr(value: &str) -> bool {
-    let num: u16 = value.parse().unwrap();
-    (1920..=2002).contains(&num)
+#[derive(Debug, PartialEq, Clone)]
+pub enum Validator {
+    /// Four digits within the range.
+    Year(RangeInclusive<u16>),
+    /// A number directly followed by one of the units, within that unit's range.
+    Units(Vec<(String, RangeInclusive<u32>)>),
+    /// `#` followed by six lowercase hex digits.
+    HexColour,
+    OneOf(Vec<String>),
+    /// Exactly this many decimal digits.
+    Digits(usize),
+    Any,
 }
 
-fn valid_iyr(value: &str) -> bool {
-    let num: u16 = value.parse().unwrap();
-    (2010..=2020).contains(&num)
+impl Validator {
+    /// `Err` says why the value was rejected.
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        match self {
+            Validator::Year(range) => {
+                if value.len() != 4 || !value.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(format!("'{}' is not a four digit year", value));
+                }
+                let year: u16 = value.parse().unwrap();
+                if range.contains(&year) {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "{} is not within {}-{}",
+                        year,
+                        range.start(),
+                        range.end()
+                    ))
+                }
+            }
+            Validator::Units(units) => {
+                let split = value
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(value.len());
+                let (number, unit) = value.split_at(split);
+                let range = units
+                    .iter()
+                    .find(|(u, _)| u == unit)
+                    .map(|(_, range)| range)
+                    .ok_or_else(|| format!("'{}' has no valid unit", value))?;
+                let number: u32 = number
+                    .parse()
+                    .map_err(|_| format!("'{}' has no number before the unit", value))?;
+                if range.contains(&number) {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "{}{} is not within {}-{}{}",
+                        number,
+                        unit,
+                        range.start(),
+                        range.end(),
+                        unit
+                    ))
+                }
+            }
+            Validator::HexColour => {
+                let hex = value.strip_prefix('#').unwrap_or("");
+                if hex.len() == 6 && hex.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+                    Ok(())
+                } else {
+                    Err(format!("'{}' is not a hex colour like #a1b2c3", value))
+                }
+            }
+            Validator::OneOf(options) => {
+                if options.iter().any(|o| o == value) {
+                    Ok(())
+                } else {
+                    Err(format!("'{}' is not one of {}", value, options.join(", ")))
+                }
+            }
+            Validator::Digits(count) => {
+                if value.len() == *count && value.bytes().all(|b| b.is_ascii_digit()) {
+                    Ok(())
+                } else {
+                    Err(format!("'{}' is not {} digits", value, count))
+                }
+            }
+            Validator::Any => Ok(()),
+        }
+    }
 }
 
-fn valid_eyr(value: &str) -> bool {
-    let num: u16 = value.parse().unwrap();
-    (2020..=2030).contains(&num)
+#[derive(Debug, PartialEq, Clone)]
+pub struct FieldRule {
+    pub name: String,
+    pub required: bool,
+    pub validator: Validator,
 }
 
-fn valid_hgt(value: &str) -> bool {
-    let matches = Regex::new(r"(\d+)(cm|in)").unwrap().captures(value);
-    if let Some(matches) = matches {
-        let num: usize = matches.get(1).unwrap().as_str().parse().unwrap();
-        match matches.get(2).map(|m| m.as_str()) {
-            Some("in") => (59..=76).contains(&num),
-            Some("cm") => (150..=193).contains(&num),
-            _ => false,
-        }
-    } else {
-        false
-    }
+#[derive(Debug, PartialEq, Clone)]
+pub struct Schema {
+    pub fields: Vec<FieldRule>,
 }
 
-fn valid_hcl(value: &str) -> bool {
-    Regex::new(r"#[0-9a-f]{6}").unwrap().is_match(value)
+#[derive(Debug, PartialEq, Clone)]
+pub struct Violation {
+    pub field: String,
+    pub reason: String,
 }
 
-fn valid_ecl(value: &str) -> bool {
-    Regex::new(r"amb|blu|brn|gry|grn|hzl|oth")
-        .unwrap()
-        .is_match(value)
+impl Schema {
+    pub fn puzzle() -> Schema {
+        PUZZLE_SCHEMA.parse().unwrap()
+    }
+
+    /// Every field of the passport that is missing or invalid, in schema order.
+    pub fn validate(&self, passport: &str) -> Result<(), Vec<Violation>> {
+        let props = parse_passport(passport);
+        let violations = self
+            .fields
+            .iter()
+            .filter_map(|rule| {
+                let reason = match props.get(rule.name.as_str()) {
+                    Some(value) => rule.validator.check(value).err()?,
+                    None if rule.required => "missing".to_string(),
+                    None => return None,
+                };
+                Some(Violation {
+                    field: rule.name.clone(),
+                    reason,
+                })
+            })
+            .collect::<Vec<_>>();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    pub fn is_valid(&self, passport: &str) -> bool {
+        self.validate(passport).is_ok()
+    }
 }
 
-fn valid_pid(value: &str) -> bool {
-    Regex::new(r"^\d{9}$").unwrap().is_match(value)
+/// One rule per line: `<field> <required|optional> <validator> [arguments]`.
+///
+/// Validators are `year MIN-MAX`, `units UNIT:MIN-MAX…`, `hex-colour`, `one-of VALUE…`,
+/// `digits COUNT` and `any`. Blank lines and lines starting with `#` are skipped.
+impl FromStr for Schema {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn range<T: FromStr>(s: &str) -> Result<RangeInclusive<T>, String> {
+            let (min, max) = s
+                .split_once('-')
+                .ok_or_else(|| format!("'{}' is not a range like 1-10", s))?;
+            let bound = |b: &str| b.parse::<T>().map_err(|_| format!("bad number '{}'", b));
+            Ok(bound(min)?..=bound(max)?)
+        }
+
+        let fields = s
+            .lines()
+            .enumerate()
+            .map(|(i, l)| (i, l.trim()))
+            .filter(|(_, l)| !l.is_empty() && !l.starts_with('#'))
+            .map(|(i, line)| -> Result<FieldRule, String> {
+                let mut words = line.split_whitespace();
+                let mut next = |what: &str| {
+                    words
+                        .next()
+                        .ok_or_else(|| format!("line {}: missing {}", i + 1, what))
+                };
+                let name = next("field name")?.to_string();
+                let required = match next("required or optional")? {
+                    "required" => true,
+                    "optional" => false,
+                    w => {
+                        return Err(format!(
+                            "line {}: expected required or optional, got '{}'",
+                            i + 1,
+                            w
+                        ))
+                    }
+                };
+                let kind = next("validator")?;
+                let args = words.collect::<Vec<_>>();
+                let validator = match (kind, args.as_slice()) {
+                    ("year", [r]) => Validator::Year(range(r)?),
+                    ("units", units) if !units.is_empty() => Validator::Units(
+                        units
+                            .iter()
+                            .map(|u| {
+                                let (unit, r) = u
+                                    .split_once(':')
+                                    .ok_or_else(|| format!("'{}' is not like cm:150-193", u))?;
+                                Ok((unit.to_string(), range(r)?))
+                            })
+                            .collect::<Result<_, String>>()?,
+                    ),
+                    ("hex-colour", []) => Validator::HexColour,
+                    ("one-of", options) if !options.is_empty() => {
+                        Validator::OneOf(options.iter().map(|o| o.to_string()).collect())
+                    }
+                    ("digits", [n]) => {
+                        Validator::Digits(n.parse().map_err(|_| format!("bad number '{}'", n))?)
+                    }
+                    ("any", []) => Validator::Any,
+                    _ => return Err(format!("line {}: bad validator '{}'", i + 1, line)),
+                };
+                Ok(FieldRule {
+                    name,
+                    required,
+                    validator,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Schema { fields })
+    }
 }
 
 pub fn matches_in_file(filename: &str) -> usize {
+    let schema = Schema::puzzle();
     let passports = get_passports(filename);
-    passports.iter().filter(|p| is_valid(p)).count()
+    passports.iter().filter(|p| schema.is_valid(p)).count()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{is_valid, matches_in_file, valid_byr, valid_ecl, valid_hcl, valid_hgt, valid_pid};
+    use crate::{get_passports, matches_in_file, Schema, Validator, Violation};
 
     #[test]
     fn field_validation() {
-        assert_eq!(true, valid_byr("2002"));
-        assert_eq!(false, valid_byr("2003"));
+        let check = |field: &str, value: &str| {
+            Schema::puzzle()
+                .fields
+                .iter()
+                .find(|f| f.name == field)
+                .unwrap()
+                .validator
+                .check(value)
+                .is_ok()
+        };
+        assert!(check("byr", "2002"));
+        assert!(!check("byr", "2003"));
+        assert!(!check("byr", "20o2"));
+        assert!(!check("byr", "02002"));
 
-        assert_eq!(true, valid_hgt("60in"));
-        assert_eq!(true, valid_hgt("190cm"));
-        assert_eq!(false, valid_hgt("190in"));
-        assert_eq!(false, valid_hgt("190"));
+        assert!(check("hgt", "60in"));
+        assert!(check("hgt", "190cm"));
+        assert!(!check("hgt", "190in"));
+        assert!(!check("hgt", "190"));
+        assert!(!check("hgt", "cm"));
+        assert!(!check("hgt", "190cmx"));
 
-        assert_eq!(true, valid_hcl("#123abc"));
-        assert_eq!(false, valid_hcl("#123abz"));
-        assert_eq!(false, valid_hcl("123abc"));
+        assert!(check("hcl", "#123abc"));
+        assert!(!check("hcl", "#123abz"));
+        assert!(!check("hcl", "123abc"));
+        assert!(!check("hcl", "#123abcd"));
 
-        assert_eq!(true, valid_ecl("brn"));
-        assert_eq!(false, valid_ecl("wat"));
+        assert!(check("ecl", "brn"));
+        assert!(!check("ecl", "wat"));
+        assert!(!check("ecl", "xamb"));
+        assert!(!check("ecl", "ambx"));
 
-        assert_eq!(true, valid_pid("000000001"));
-        assert_eq!(false, valid_pid("0123456789"));
+        assert!(check("pid", "000000001"));
+        assert!(!check("pid", "0123456789"));
     }
 
     macro_rules! passport_tests {
@@ -111,7 +297,7 @@ mod tests {
             #[test]
             fn $name() {
                 let (expected,input) = $value;
-                assert_eq!(expected, is_valid(input));
+                assert_eq!(expected, Schema::puzzle().is_valid(input));
             }
         )*
         }
@@ -124,9 +310,77 @@ mod tests {
         t4: (false, "hcl:#cfa07d eyr:2025 pid:166559648\niyr:2011 ecl:brn hgt:59in"),
     }
 
+    #[test]
+    fn violations() {
+        assert_eq!(
+            Err(vec![
+                Violation {
+                    field: "byr".to_string(),
+                    reason: "'19x7' is not a four digit year".to_string()
+                },
+                Violation {
+                    field: "hgt".to_string(),
+                    reason: "missing".to_string()
+                },
+                Violation {
+                    field: "ecl".to_string(),
+                    reason: "'xamb' is not one of amb, blu, brn, gry, grn, hzl, oth".to_string()
+                },
+            ]),
+            Schema::puzzle()
+                .validate("ecl:xamb pid:860033327 eyr:2020 hcl:#fffffd\nbyr:19x7 iyr:2017 cid:147")
+        );
+    }
+
+    #[test]
+    fn custom_schema() {
+        let schema: Schema = "\
+# a comment
+name required any
+
+age optional units y:0-150 m:0-1800
+"
+        .parse()
+        .unwrap();
+        assert_eq!(2, schema.fields.len());
+        assert_eq!(
+            Validator::Units(vec![
+                ("y".to_string(), 0..=150),
+                ("m".to_string(), 0..=1800)
+            ]),
+            schema.fields[1].validator
+        );
+        assert!(schema.is_valid("name:x"));
+        assert!(schema.is_valid("name:x age:40y"));
+        assert!(!schema.is_valid("name:x age:200y"));
+        assert!(!schema.is_valid("age:40y"));
+
+        assert!("name sometimes any".parse::<Schema>().is_err());
+        assert!("name required year 1900".parse::<Schema>().is_err());
+        assert!("name required colour".parse::<Schema>().is_err());
+        assert!("name required".parse::<Schema>().is_err());
+    }
+
+    #[test]
+    fn get_passports_line_endings() {
+        // removed with its files when dropped
+        let dir = tempfile::tempdir().unwrap();
+        let lf = dir.path().join("lf.txt");
+        let crlf = dir.path().join("crlf.txt");
+        let input = std::fs::read_to_string("../../examples/2020/day04/example.txt").unwrap();
+        std::fs::write(&lf, input.replace("\r\n", "\n").replace("\n\n", "\n  \n")).unwrap();
+        std::fs::write(&crlf, input.replace("\r\n", "\n").replace('\n', "\r\n")).unwrap();
+        assert_eq!(4, get_passports(lf.to_str().unwrap()).len());
+        assert_eq!(
+            get_passports(lf.to_str().unwrap()),
+            get_passports(crlf.to_str().unwrap())
+        );
+        assert_eq!(2, matches_in_file(crlf.to_str().unwrap()));
+    }
+
     #[test]
     fn example() {
-        assert_eq!(2, matches_in_file("example.txt"));
+        assert_eq!(2, matches_in_file("../../examples/2020/day04/example.txt"));
     }
     //#[test]
     // fn part1() {
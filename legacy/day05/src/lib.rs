@@ -1,66 +1,234 @@
 use std::{
-    collections::HashSet,
+    collections::BTreeSet,
+    fmt::{self, Display},
     fs::File,
     io::{self, BufRead},
     path::Path,
+    str::FromStr,
 };
 
-fn split_interval((lower, upper): (i32, i32), split: char) -> (i32, i32) {
-    let middle = (lower + upper + 1) / 2;
-    println!(
-        "Splitting {} {}..{}  {}..{}",
-        split,
-        lower,
-        middle - 1,
-        middle,
-        upper
-    );
-    if lower == upper {
-        panic!("Can't split interval of 1");
+use tracing::{debug, trace};
+
+/// How many bits of a pass select the row (`F`/`B`) and how many the column (`L`/`R`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Layout {
+    row_bits: u32,
+    column_bits: u32,
+}
+
+impl Layout {
+    /// `None` unless the row and column fit in 32 bits together.
+    pub fn new(row_bits: u32, column_bits: u32) -> Option<Layout> {
+        if row_bits.checked_add(column_bits)? <= 32 {
+            Some(Layout {
+                row_bits,
+                column_bits,
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn row_bits(&self) -> u32 {
+        self.row_bits
+    }
+
+    pub fn column_bits(&self) -> u32 {
+        self.column_bits
+    }
+}
+
+impl Default for Layout {
+    /// 128 rows of 8 seats, as on the puzzle's plane.
+    fn default() -> Self {
+        Layout {
+            row_bits: 7,
+            column_bits: 3,
+        }
+    }
+}
+
+/// A seat, encoded as `FBFBBFFRLR`: the letters are the bits of the row and the column,
+/// `B` and `R` being 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BoardingPass {
+    row: u32,
+    column: u32,
+    layout: Layout,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    Length {
+        expected: usize,
+        found: usize,
+    },
+    /// 0-based position of the unexpected char.
+    UnknownChar {
+        position: usize,
+        found: char,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Length { expected, found } => {
+                write!(f, "expected {} letters, found {}", expected, found)
+            }
+            ParseError::UnknownChar { position, found } => {
+                write!(f, "unexpected '{}' at position {}", found, position)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl BoardingPass {
+    /// `None` if the row or column does not fit the layout.
+    pub fn new(row: u32, column: u32, layout: Layout) -> Option<BoardingPass> {
+        if u64::from(row) >> layout.row_bits == 0 && u64::from(column) >> layout.column_bits == 0 {
+            Some(BoardingPass {
+                row,
+                column,
+                layout,
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn parse(s: &str, layout: Layout) -> Result<BoardingPass, ParseError> {
+        let expected = (layout.row_bits + layout.column_bits) as usize;
+        if s.chars().count() != expected {
+            return Err(ParseError::Length {
+                expected,
+                found: s.chars().count(),
+            });
+        }
+        fn bits(
+            mut chars: impl Iterator<Item = (usize, char)>,
+            zero: char,
+            one: char,
+        ) -> Result<u32, ParseError> {
+            chars.try_fold(0, |acc, (position, c)| {
+                // each letter keeps the lower or the upper half of the seats left
+                let acc = match c {
+                    c if c == zero => acc << 1,
+                    c if c == one => acc << 1 | 1,
+                    found => return Err(ParseError::UnknownChar { position, found }),
+                };
+                trace!(position, half = %c, so_far = acc);
+                Ok(acc)
+            })
+        }
+        let mut chars = s.chars().enumerate();
+        let row = bits(chars.by_ref().take(layout.row_bits as usize), 'F', 'B')?;
+        let column = bits(chars, 'L', 'R')?;
+        debug!(pass = s, row, column, "decoded");
+        Ok(BoardingPass {
+            row,
+            column,
+            layout,
+        })
+    }
+
+    pub fn row(&self) -> u32 {
+        self.row
+    }
+
+    pub fn column(&self) -> u32 {
+        self.column
+    }
+
+    /// `row * 8 + column` for the default layout, i.e. the whole pass read as one binary number.
+    pub fn id(&self) -> u64 {
+        u64::from(self.row) << self.layout.column_bits | u64::from(self.column)
+    }
+}
+
+impl FromStr for BoardingPass {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BoardingPass::parse(s, Layout::default())
     }
-    match split {
-        'F' | 'L' => (lower, middle - 1),
-        'B' | 'R' => (middle, upper),
-        _ => panic!("Unknown char: {}", split),
+}
+
+impl Display for BoardingPass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for bit in (0..self.layout.row_bits).rev() {
+            f.write_str(if self.row >> bit & 1 == 1 { "B" } else { "F" })?;
+        }
+        for bit in (0..self.layout.column_bits).rev() {
+            f.write_str(if self.column >> bit & 1 == 1 {
+                "R"
+            } else {
+                "L"
+            })?;
+        }
+        Ok(())
     }
 }
 
-fn get(pass: &str, max: i32) -> i32 {
-    println!("Starting split 0..{}\t{}", max, pass);
-    let (lower, upper) = pass.chars().into_iter().fold((0, max), split_interval);
-    assert_eq!(lower, upper);
-    lower
+/// Which seat ids are taken.
+#[derive(Debug, Default)]
+pub struct SeatMap {
+    taken: BTreeSet<u64>,
 }
 
-fn get_row_column_id(pass: &str) -> (i32, i32, i32) {
-    let row = get(&pass[..7], 127);
-    let column = get(&pass[7..], 7);
-    let id = row * 8 + column;
-    (row, column, id)
+impl SeatMap {
+    pub fn new<'a>(passes: impl IntoIterator<Item = &'a BoardingPass>) -> SeatMap {
+        SeatMap {
+            taken: passes.into_iter().map(BoardingPass::id).collect(),
+        }
+    }
+
+    /// Every free id between the lowest and the highest taken one.
+    pub fn gaps(&self) -> Vec<u64> {
+        self.taken
+            .iter()
+            .zip(self.taken.iter().skip(1))
+            .flat_map(|(a, b)| a + 1..*b)
+            .collect()
+    }
+
+    /// Free ids whose neighbours on both sides are taken.
+    pub fn single_gaps(&self) -> Vec<u64> {
+        self.gaps()
+            .into_iter()
+            .filter(|id| self.taken.contains(&(id - 1)) && self.taken.contains(&(id + 1)))
+            .collect()
+    }
 }
 
-pub fn highest_seat_id(passfile: &str) -> i32 {
+fn read_passes(passfile: &str) -> Vec<BoardingPass> {
     read_lines(passfile)
         .unwrap()
-        .map(|s| get_row_column_id(s.unwrap().as_str()).2)
+        .map(|s| s.unwrap().parse().unwrap())
+        .collect()
+}
+
+pub fn get_row_column_id(pass: &str) -> (u32, u32, u64) {
+    let pass: BoardingPass = pass.parse().unwrap();
+    (pass.row(), pass.column(), pass.id())
+}
+
+pub fn highest_seat_id(passfile: &str) -> u64 {
+    read_passes(passfile)
+        .iter()
+        .map(BoardingPass::id)
         .max()
         .unwrap()
 }
 
-pub fn get_missing_seat(passfile: &str) -> i32 {
-    let seat_ids = read_lines(passfile)
-        .unwrap()
-        .map(|s| get_row_column_id(s.unwrap().as_str()).2)
-        .collect::<HashSet<i32>>();
-    println!("Seats: {:?}", seat_ids);
-    (0..823)
-        .filter(|id| {
-            !seat_ids.contains(id) && seat_ids.contains(&(id - 1)) && seat_ids.contains(&(id + 1))
-        })
-        .collect::<Vec<_>>()
-        .first()
-        .unwrap()
-        .to_owned()
+/// `None` if no seat between two taken ones is free.
+pub fn get_missing_seat(passfile: &str) -> Option<u64> {
+    let seats = SeatMap::new(&read_passes(passfile));
+    let gaps = seats.single_gaps();
+    debug!(taken = seats.taken.len(), ?gaps, "looked for free seats");
+    gaps.first().copied()
 }
 
 fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
@@ -73,13 +241,108 @@ where
 
 #[cfg(test)]
 mod tests {
-    use std::assert_eq;
+    use std::{assert_eq, fs::read_to_string};
+
+    use crate::{
+        get_missing_seat, get_row_column_id, highest_seat_id, BoardingPass, Layout, ParseError,
+        SeatMap,
+    };
+
+    #[test]
+    fn rows_and_columns() {
+        let example = read_to_string("../../examples/2020/day05/example.txt").unwrap();
+        assert_eq!(
+            vec![(44, 5), (70, 7), (14, 7), (102, 4)],
+            example
+                .lines()
+                .map(|pass| {
+                    let (row, column, _) = get_row_column_id(pass);
+                    (row, column)
+                })
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let layout = Layout::default();
+        for row in 0..128 {
+            for column in 0..8 {
+                let pass = BoardingPass::new(row, column, layout).unwrap();
+                assert_eq!(Ok(pass), pass.to_string().parse());
+                assert_eq!(u64::from(row * 8 + column), pass.id());
+            }
+        }
+        assert_eq!(
+            "FBFBBFFRLR",
+            "FBFBBFFRLR".parse::<BoardingPass>().unwrap().to_string()
+        );
+    }
 
-    use crate::{get_missing_seat, get_row_column_id, highest_seat_id};
+    #[test]
+    fn other_layouts() {
+        let layout = Layout::new(2, 4).unwrap();
+        let pass = BoardingPass::parse("BFLRRL", layout).unwrap();
+        assert_eq!((2, 6, 38), (pass.row(), pass.column(), pass.id()));
+        assert_eq!("BFLRRL", pass.to_string());
+        assert_eq!(None, BoardingPass::new(4, 0, layout));
+        assert_eq!(None, BoardingPass::new(0, 16, layout));
+
+        let layout = Layout::new(0, 1).unwrap();
+        assert_eq!(1, BoardingPass::parse("R", layout).unwrap().id());
+
+        let widest = Layout::new(16, 16).unwrap();
+        let pass = BoardingPass::new(u16::MAX.into(), 1, widest).unwrap();
+        assert_eq!(u64::from(u16::MAX) << 16 | 1, pass.id());
+        assert_eq!(Ok(pass), BoardingPass::parse(&pass.to_string(), widest));
+        assert_eq!(None, Layout::new(16, 17));
+        assert_eq!(None, Layout::new(33, 0));
+        assert_eq!(None, Layout::new(u32::MAX, 1));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            Err(ParseError::Length {
+                expected: 10,
+                found: 9
+            }),
+            "FBFBBFFRL".parse::<BoardingPass>()
+        );
+        assert_eq!(
+            Err(ParseError::UnknownChar {
+                position: 3,
+                found: 'X'
+            }),
+            "FBFXBFFRLR".parse::<BoardingPass>()
+        );
+        assert_eq!(
+            Err(ParseError::UnknownChar {
+                position: 7,
+                found: 'F'
+            }),
+            "FBFBBFFFLR".parse::<BoardingPass>()
+        );
+        assert_eq!(
+            Err(ParseError::UnknownChar {
+                position: 0,
+                found: 'R'
+            }),
+            "RBFBBFFRLR".parse::<BoardingPass>()
+        );
+    }
 
     #[test]
-    fn example() {
-        assert_eq!((44, 5, 357), get_row_column_id("FBFBBFFRLR"));
+    fn gaps() {
+        let layout = Layout::default();
+        let passes = [3, 4, 6, 9, 10, 12]
+            .iter()
+            .map(|id| BoardingPass::new(id / 8, id % 8, layout).unwrap())
+            .collect::<Vec<_>>();
+        let seats = SeatMap::new(&passes);
+        assert_eq!(vec![5, 7, 8, 11], seats.gaps());
+        assert_eq!(vec![5, 11], seats.single_gaps());
+        assert!(SeatMap::new(&[]).gaps().is_empty());
     }
 
     #[test]
@@ -88,6 +351,6 @@ mod tests {
     }
     #[test]
     fn part2() {
-        assert_eq!(705, get_missing_seat("input.txt"));
+        assert_eq!(Some(705), get_missing_seat("input.txt"));
     }
 }
@@ -1,51 +1,161 @@
-use std::{collections::HashSet, fs::read_to_string};
+use records::records;
+use std::{
+    fmt::{self, Display},
+    fs::read_to_string,
+    str::FromStr,
+};
 
-#[cfg(windows)]
-const LINE_ENDING: &str = "\r\n";
-#[cfg(not(windows))]
-const LINE_ENDING: &'static str = "\n";
+/// Questions answered "yes", bit `i` standing for the question `'a' + i`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Answers(u32);
 
-pub fn sum_yes_answers(filename: &str) -> usize {
-    read_to_string(filename)
-        .unwrap()
-        .split(LINE_ENDING.repeat(2).as_str())
-        .map(unique_answer_count)
-        .sum::<usize>()
+/// A char outside `a..=z`, with its 0-based position.
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    /// Index of the group, only set by `parse_groups`.
+    pub group: usize,
+    pub person: usize,
+    pub column: usize,
+    pub found: char,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "group {}, person {}, column {}: '{}' is not a question between a and z",
+            self.group, self.person, self.column, self.found
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl Answers {
+    pub const ALL: Answers = Answers((1 << 26) - 1);
+
+    pub fn contains(self, question: char) -> bool {
+        question.is_ascii_lowercase() && self.0 & 1 << (question as u8 - b'a') != 0
+    }
+
+    pub fn count(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn union(self, other: Answers) -> Answers {
+        Answers(self.0 | other.0)
+    }
+
+    pub fn intersection(self, other: Answers) -> Answers {
+        Answers(self.0 & other.0)
+    }
 }
 
-fn unique_answer_count(answers: &str) -> usize {
-    answers
-        .chars()
-        .filter(|c| c.is_ascii_alphabetic())
-        .collect::<HashSet<_>>()
-        .len()
+impl FromStr for Answers {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.chars()
+            .enumerate()
+            .try_fold(Answers::default(), |answers, (column, c)| {
+                if c.is_ascii_lowercase() {
+                    Ok(Answers(answers.0 | 1 << (c as u8 - b'a')))
+                } else {
+                    Err(ParseError {
+                        group: 0,
+                        person: 0,
+                        column,
+                        found: c,
+                    })
+                }
+            })
+    }
 }
 
-fn all_yes_answer_count(answers: &str) -> usize {
-    answers
-        .lines()
-        .fold(None, |acc, c| -> Option<HashSet<char>> {
-            let answers = c.chars().collect::<HashSet<_>>();
-            Some(match acc {
-                None => answers,
-                Some(h) => answers.intersection(&h).cloned().collect::<HashSet<_>>(),
+/// Everyone's answers in one group, one person per line.
+#[derive(Debug, Default, PartialEq)]
+pub struct Group {
+    people: Vec<Answers>,
+}
+
+impl Group {
+    pub fn parse(people: &[&str]) -> Result<Group, ParseError> {
+        people
+            .iter()
+            .enumerate()
+            .map(|(person, line)| {
+                line.parse()
+                    .map_err(|e: ParseError| ParseError { person, ..e })
             })
-        })
+            .collect::<Result<_, _>>()
+            .map(|people| Group { people })
+    }
+
+    /// Questions anyone answered.
+    pub fn anyone(&self) -> Answers {
+        self.people
+            .iter()
+            .fold(Answers::default(), |acc, a| acc.union(*a))
+    }
+
+    /// Questions everyone answered, none for an empty group.
+    pub fn everyone(&self) -> Answers {
+        if self.people.is_empty() {
+            return Answers::default();
+        }
+        self.people
+            .iter()
+            .fold(Answers::ALL, |acc, a| acc.intersection(*a))
+    }
+
+    fn answered_by(&self, matches: impl Fn(usize) -> bool) -> Answers {
+        Answers((0..26).fold(0, |acc, bit| {
+            let count = self.people.iter().filter(|a| a.0 & 1 << bit != 0).count();
+            if matches(count) {
+                acc | 1 << bit
+            } else {
+                acc
+            }
+        }))
+    }
+
+    /// Questions answered by exactly `k` people.
+    pub fn exactly(&self, k: usize) -> Answers {
+        self.answered_by(|count| count == k)
+    }
+
+    /// Questions answered by `k` or more people.
+    pub fn at_least(&self, k: usize) -> Answers {
+        self.answered_by(|count| count >= k)
+    }
+}
+
+pub fn parse_groups(input: &str) -> Result<Vec<Group>, ParseError> {
+    records(input)
+        .enumerate()
+        .map(|(group, people)| Group::parse(&people).map_err(|e| ParseError { group, ..e }))
+        .collect()
+}
+
+pub fn sum_yes_answers(filename: &str) -> usize {
+    parse_groups(&read_to_string(filename).unwrap())
         .unwrap()
-        .len()
+        .iter()
+        .map(|group| group.anyone().count())
+        .sum::<usize>()
 }
 
 pub fn sum_all_yes_answers(filename: &str) -> usize {
-    read_to_string(filename)
+    parse_groups(&read_to_string(filename).unwrap())
         .unwrap()
-        .split(LINE_ENDING.repeat(2).as_str())
-        .map(all_yes_answer_count)
+        .iter()
+        .map(|group| group.everyone().count())
         .sum::<usize>()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{sum_all_yes_answers, sum_yes_answers};
+    use crate::{parse_groups, sum_all_yes_answers, sum_yes_answers, Answers, Group, ParseError};
 
     #[test]
     fn part1() {
@@ -54,11 +164,63 @@ mod tests {
 
     #[test]
     fn part2_example() {
-        assert_eq!(6, sum_all_yes_answers("example.txt"));
+        assert_eq!(
+            6,
+            sum_all_yes_answers("../../examples/2020/day06/example.txt")
+        );
     }
 
     #[test]
     fn part2() {
         assert_eq!(3052, sum_all_yes_answers("input.txt"));
     }
+
+    #[test]
+    fn answers() {
+        let a: Answers = "abcz".parse().unwrap();
+        let b: Answers = "cxz".parse().unwrap();
+        assert_eq!(4, a.count());
+        assert!(a.contains('z') && !a.contains('x') && !a.contains('A'));
+        assert_eq!("abcxz".parse(), Ok(a.union(b)));
+        assert_eq!("cz".parse(), Ok(a.intersection(b)));
+        assert_eq!(26, Answers::ALL.count());
+        assert_eq!(Ok(Answers::default()), "".parse());
+    }
+
+    #[test]
+    fn queries() {
+        let group = Group::parse(&["abc", "ab", "a", "d"]).unwrap();
+        assert_eq!("abcd".parse(), Ok(group.anyone()));
+        assert_eq!(Answers::default(), group.everyone());
+        assert_eq!("cd".parse(), Ok(group.exactly(1)));
+        assert_eq!("b".parse(), Ok(group.exactly(2)));
+        assert_eq!("a".parse(), Ok(group.exactly(3)));
+        assert_eq!("ab".parse(), Ok(group.at_least(2)));
+        assert_eq!(group.anyone(), group.at_least(1));
+        assert_eq!(Answers::default(), group.at_least(5));
+    }
+
+    #[test]
+    fn empty_group() {
+        let group = Group::default();
+        assert_eq!(Answers::default(), group.anyone());
+        assert_eq!(Answers::default(), group.everyone());
+        assert_eq!(Answers::default(), group.at_least(1));
+        assert!(parse_groups("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            Err(ParseError {
+                group: 1,
+                person: 2,
+                column: 1,
+                found: 'B'
+            }),
+            parse_groups("abc\n\na\nb\ncB\n\nd")
+        );
+        assert!(parse_groups("a-b").is_err());
+        assert!(parse_groups("ab c").is_err());
+    }
 }
@@ -1,15 +1,13 @@
 use nom::bytes::complete::tag;
 use nom::{branch::alt, multi::separated_list1};
-use nom::{bytes::complete::take_while, combinator::map};
-use nom::{sequence::*, IResult};
-use std::{
-    collections::HashMap,
-    fs::File,
-    io::{self, BufRead},
-    path::Path,
+use nom::{
+    bytes::complete::take_while,
+    combinator::{all_consuming, map, map_res},
 };
+use nom::{sequence::*, IResult};
+use std::collections::HashMap;
 
-#[derive(Debug, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct Bag {
     modifier: String,
     color: String,
@@ -21,7 +19,7 @@ impl std::fmt::Display for Bag {
     }
 }
 
-type Rule = (Bag, Vec<(u32, Bag)>);
+pub type Rule = (Bag, Vec<(u32, Bag)>);
 
 fn parse_bag(input: &str) -> IResult<&str, Bag> {
     let space = tag(" ");
@@ -42,9 +40,9 @@ fn parse_bag(input: &str) -> IResult<&str, Bag> {
 
 fn parse_bag_count(input: &str) -> IResult<&str, (u32, Bag)> {
     let space = tag(" ");
-    let count = map(
+    let count = map_res(
         terminated(nom::character::complete::digit1, space),
-        |r: &str| r.parse::<u32>().unwrap(),
+        |r: &str| r.parse::<u32>(),
     );
     tuple((count, parse_bag))(input)
 }
@@ -63,82 +61,316 @@ fn parse_rule(input: &str) -> IResult<&str, Rule> {
     tuple((parse_antecedent, parse_consequent))(input)
 }
 
-fn get_rules(filename: &str) -> std::vec::Vec<(Bag, std::vec::Vec<(u32, Bag)>)> {
-    read_lines(filename)
-        .unwrap()
-        .into_iter()
-        .map(|l| {
-            let l = &l.unwrap();
-            parse_rule(l).unwrap().1
-        })
-        .collect::<Vec<_>>()
+#[derive(Debug, PartialEq)]
+pub enum GraphError {
+    /// 1-based line number and the text that could not be parsed.
+    Parse(usize, String),
+    DuplicateRule(Bag),
+    /// A bag that is contained in another one, but has no rule of its own.
+    UndefinedBag(Bag),
+    /// Bags that end up containing themselves, the first one repeated at the end.
+    Cycle(Vec<Bag>),
+    /// The number of bags inside does not fit into a `u64`.
+    Overflow(Bag),
 }
 
-pub fn get_bags_with_gold_in(filename: &str) -> Vec<Bag> {
-    let rules = get_rules(filename);
-    let rules_lookup = get_rules(filename)
-        .into_iter()
-        .collect::<HashMap<Bag, Vec<(u32, Bag)>>>();
-    rules
-        .into_iter()
-        .map(|(b, _)| b)
-        .filter(|b| can_contain_gold(b, &rules_lookup))
-        .collect()
+impl std::fmt::Display for GraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphError::Parse(line, text) => write!(f, "line {}: cannot parse '{}'", line, text),
+            GraphError::DuplicateRule(bag) => write!(f, "{} has more than one rule", bag),
+            GraphError::UndefinedBag(bag) => write!(f, "{} has no rule", bag),
+            GraphError::Cycle(bags) => {
+                f.write_str("bags contain themselves: ")?;
+                for (i, bag) in bags.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" -> ")?;
+                    }
+                    write!(f, "{}", bag)?;
+                }
+                Ok(())
+            }
+            GraphError::Overflow(bag) => write!(f, "too many bags inside {}", bag),
+        }
+    }
 }
 
-fn golden() -> Bag {
-    Bag {
-        modifier: "shiny".to_string(),
-        color: "gold".to_string(),
+impl std::error::Error for GraphError {}
+
+impl Bag {
+    pub fn new(modifier: &str, color: &str) -> Bag {
+        Bag {
+            modifier: modifier.to_string(),
+            color: color.to_string(),
+        }
+    }
+
+    fn dot_name(&self) -> String {
+        format!("\"{} {}\"", self.modifier, self.color)
     }
 }
 
-fn can_contain_gold(bag: &Bag, rules: &HashMap<Bag, Vec<(u32, Bag)>>) -> bool {
-    if bag == &golden() {
-        true
-    } else {
-        let rule_contains = rules.get(&bag).unwrap();
-        for (_, contained_bag) in rule_contains {
-            if can_contain_gold(contained_bag, rules) {
-                return true;
+pub type BagId = usize;
+
+/// All rules, with every bag interned to a `BagId` indexing the vectors.
+///
+/// Building the graph rejects undefined bags and containment cycles, so the queries can't fail
+/// on them later.
+#[derive(Debug)]
+pub struct BagGraph {
+    bags: Vec<Bag>,
+    ids: HashMap<Bag, BagId>,
+    contents: Vec<Vec<(u32, BagId)>>,
+    containers: Vec<Vec<BagId>>,
+    /// Bags inside each bag, `None` on overflow; filled in topological order.
+    inside: Vec<Option<u64>>,
+}
+
+impl BagGraph {
+    pub fn parse(input: &str) -> Result<BagGraph, GraphError> {
+        let rules = input
+            .lines()
+            .enumerate()
+            .map(|(i, l)| match all_consuming(parse_rule)(l) {
+                Ok((_, rule)) => Ok(rule),
+                Err(_) => Err(GraphError::Parse(i + 1, l.to_string())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        BagGraph::from_rules(rules)
+    }
+
+    pub fn from_rules(rules: Vec<Rule>) -> Result<BagGraph, GraphError> {
+        let mut ids = HashMap::with_capacity(rules.len());
+        for (id, (bag, _)) in rules.iter().enumerate() {
+            if ids.insert(bag.clone(), id).is_some() {
+                return Err(GraphError::DuplicateRule(bag.clone()));
+            }
+        }
+        let mut bags = Vec::with_capacity(rules.len());
+        let mut contents = Vec::with_capacity(rules.len());
+        let mut containers = vec![Vec::new(); rules.len()];
+        for (id, (bag, inside)) in rules.into_iter().enumerate() {
+            let inside = inside
+                .into_iter()
+                .map(|(count, b)| match ids.get(&b) {
+                    Some(&b_id) => {
+                        containers[b_id].push(id);
+                        Ok((count, b_id))
+                    }
+                    None => Err(GraphError::UndefinedBag(b)),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            bags.push(bag);
+            contents.push(inside);
+        }
+        let mut graph = BagGraph {
+            bags,
+            ids,
+            contents,
+            containers,
+            inside: Vec::new(),
+        };
+        graph.inside = graph.count_all_inside()?;
+        Ok(graph)
+    }
+
+    /// Depth-first search in post-order, which both finds cycles and lets every bag be counted
+    /// after everything inside it.
+    fn count_all_inside(&self) -> Result<Vec<Option<u64>>, GraphError> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum State {
+            New,
+            Open,
+            Done,
+        }
+        let mut state = vec![State::New; self.bags.len()];
+        let mut inside: Vec<Option<u64>> = vec![Some(0); self.bags.len()];
+        for root in 0..self.bags.len() {
+            if state[root] != State::New {
+                continue;
+            }
+            // (bag, index of the next content to visit)
+            let mut stack = vec![(root, 0)];
+            state[root] = State::Open;
+            while let Some((bag, next)) = stack.last_mut() {
+                let bag = *bag;
+                if let Some(&(_, child)) = self.contents[bag].get(*next) {
+                    *next += 1;
+                    match state[child] {
+                        State::New => {
+                            state[child] = State::Open;
+                            stack.push((child, 0));
+                        }
+                        State::Open => {
+                            let start = stack.iter().position(|(b, _)| *b == child).unwrap();
+                            let mut cycle = stack[start..]
+                                .iter()
+                                .map(|(b, _)| self.bags[*b].clone())
+                                .collect::<Vec<_>>();
+                            cycle.push(self.bags[child].clone());
+                            return Err(GraphError::Cycle(cycle));
+                        }
+                        State::Done => {}
+                    }
+                } else {
+                    inside[bag] =
+                        self.contents[bag]
+                            .iter()
+                            .try_fold(0u64, |sum, &(count, child)| {
+                                inside[child]?
+                                    .checked_add(1)?
+                                    .checked_mul(u64::from(count))?
+                                    .checked_add(sum)
+                            });
+                    state[bag] = State::Done;
+                    stack.pop();
+                }
+            }
+        }
+        Ok(inside)
+    }
+
+    pub fn id(&self, bag: &Bag) -> Option<BagId> {
+        self.ids.get(bag).copied()
+    }
+
+    pub fn bag(&self, id: BagId) -> &Bag {
+        &self.bags[id]
+    }
+
+    fn existing_id(&self, bag: &Bag) -> Result<BagId, GraphError> {
+        self.id(bag)
+            .ok_or_else(|| GraphError::UndefinedBag(bag.clone()))
+    }
+
+    /// Ids reachable from `start` following `edges`, not including `start`.
+    fn reachable(&self, start: BagId, edges: impl Fn(BagId) -> Vec<BagId>) -> Vec<bool> {
+        let mut seen = vec![false; self.bags.len()];
+        let mut stack = edges(start);
+        while let Some(id) = stack.pop() {
+            if !seen[id] {
+                seen[id] = true;
+                stack.extend(edges(id));
+            }
+        }
+        seen
+    }
+
+    fn containers_mask(&self, id: BagId) -> Vec<bool> {
+        self.reachable(id, |b| self.containers[b].clone())
+    }
+
+    fn contents_mask(&self, id: BagId) -> Vec<bool> {
+        self.reachable(id, |b| self.contents[b].iter().map(|(_, c)| *c).collect())
+    }
+
+    /// Every bag that can eventually contain `bag`.
+    pub fn containers_of(&self, bag: &Bag) -> Result<Vec<&Bag>, GraphError> {
+        let mask = self.containers_mask(self.existing_id(bag)?);
+        Ok(self
+            .bags
+            .iter()
+            .zip(mask)
+            .filter(|(_, can)| *can)
+            .map(|(b, _)| b)
+            .collect())
+    }
+
+    /// How many bags `bag` has to contain, not counting itself.
+    pub fn count_inside(&self, bag: &Bag) -> Result<u64, GraphError> {
+        self.inside[self.existing_id(bag)?].ok_or_else(|| GraphError::Overflow(bag.clone()))
+    }
+
+    fn dot(&self, include: impl Fn(BagId) -> bool, edge: impl Fn(BagId, BagId) -> bool) -> String {
+        let mut dot = String::from("digraph bags {\n");
+        for (id, bag) in self.bags.iter().enumerate().filter(|(id, _)| include(*id)) {
+            dot += &format!("  {};\n", bag.dot_name());
+            for &(count, child) in &self.contents[id] {
+                if edge(id, child) {
+                    dot += &format!(
+                        "  {} -> {} [label={}];\n",
+                        bag.dot_name(),
+                        self.bags[child].dot_name(),
+                        count
+                    );
+                }
             }
         }
-        false
+        dot += "}\n";
+        dot
+    }
+
+    /// Graphviz DOT of every rule, edges labelled with the count.
+    pub fn to_dot(&self) -> String {
+        self.dot(|_| true, |_, _| true)
+    }
+
+    /// Graphviz DOT of `bag` with everything that can contain it and everything inside it.
+    pub fn to_dot_around(&self, bag: &Bag) -> Result<String, GraphError> {
+        let id = self.existing_id(bag)?;
+        let mut up = self.containers_mask(id);
+        let mut down = self.contents_mask(id);
+        up[id] = true;
+        down[id] = true;
+        let dot = self.dot(
+            |b| up[b] || down[b],
+            |from, to| (up[from] && up[to]) || (down[from] && down[to]),
+        );
+        Ok(dot.replacen(
+            &format!("  {};\n", bag.dot_name()),
+            &format!("  {} [style=filled];\n", bag.dot_name()),
+            1,
+        ))
     }
 }
 
-pub fn get_bags_in_gold(filename: &str) -> u32 {
-    let rules = get_rules(filename)
-        .into_iter()
-        .collect::<HashMap<Bag, Vec<(u32, Bag)>>>();
-    get_bags_in(&golden(), &rules)
+fn get_graph(filename: &str) -> BagGraph {
+    BagGraph::parse(&std::fs::read_to_string(filename).unwrap()).unwrap()
 }
 
-fn get_bags_in(bag: &Bag, rules: &HashMap<Bag, Vec<(u32, Bag)>>) -> u32 {
-    let inside: u32 = rules
-        .get(&bag)
+/// Bags that can hold a shiny gold bag, plus the shiny gold bag itself.
+pub fn get_bags_with_gold_in(filename: &str) -> Vec<Bag> {
+    let graph = get_graph(filename);
+    let mut bags = graph
+        .containers_of(&golden())
         .unwrap()
-        .iter()
-        .map(|(count, bag_in)| get_bags_in(bag_in, rules) * count)
-        .sum();
-    1 + inside
+        .into_iter()
+        .cloned()
+        .collect::<Vec<_>>();
+    bags.push(golden());
+    bags
+}
+
+fn golden() -> Bag {
+    Bag::new("shiny", "gold")
 }
 
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where
-    P: AsRef<Path>,
-{
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+/// Bags inside a shiny gold bag, plus the shiny gold bag itself.
+pub fn get_bags_in_gold(filename: &str) -> u64 {
+    1 + get_graph(filename).count_inside(&golden()).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{
+        fs::{read_to_string, File},
+        io::{self, BufRead},
+        path::Path,
+    };
+
     use crate::{
         get_bags_in_gold, get_bags_with_gold_in, parse_antecedent, parse_bag, parse_consequent,
-        parse_rule, read_lines, Bag,
+        parse_rule, Bag, BagGraph, GraphError,
     };
 
+    fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
+    where
+        P: AsRef<Path>,
+    {
+        let file = File::open(filename)?;
+        Ok(io::BufReader::new(file).lines())
+    }
+
     #[test]
     fn parse_bag_example_short() {
         assert_eq!(
@@ -208,7 +440,7 @@ mod tests {
 
     #[test]
     fn parse_rule_example() {
-        for line in read_lines("example.txt").unwrap() {
+        for line in read_lines("../../examples/2020/day07/example.txt").unwrap() {
             let line = &line.unwrap();
             let result = parse_rule(line).unwrap();
             assert_eq!(result.0, "");
@@ -216,23 +448,23 @@ mod tests {
     }
     #[test]
     fn get_bags_with_gold_in_example() {
-        let count = get_bags_with_gold_in("example.txt").into_iter().count();
+        let count = get_bags_with_gold_in("../../examples/2020/day07/example.txt").len();
         assert_eq!(4, count - 1);
     }
     #[test]
     fn get_bags_with_gold_in_input() {
-        let count = get_bags_with_gold_in("input.txt").into_iter().count();
+        let count = get_bags_with_gold_in("input.txt").len();
         assert_eq!(252, count - 1);
     }
 
     #[test]
     fn get_bags_in_gold_example() {
-        let count = get_bags_in_gold("example.txt");
+        let count = get_bags_in_gold("../../examples/2020/day07/example.txt");
         assert_eq!(32, count - 1);
     }
     #[test]
     fn get_bags_in_gold_example2() {
-        let count = get_bags_in_gold("example2.txt");
+        let count = get_bags_in_gold("../../examples/2020/day07/deep.txt");
         assert_eq!(126, count - 1);
     }
     #[test]
@@ -240,4 +472,104 @@ mod tests {
         let count = get_bags_in_gold("input.txt");
         assert_eq!(35487, count - 1);
     }
+
+    #[test]
+    fn any_bag() {
+        let graph =
+            BagGraph::parse(&read_to_string("../../examples/2020/day07/example.txt").unwrap())
+                .unwrap();
+        let mut containers = graph
+            .containers_of(&Bag::new("faded", "blue"))
+            .unwrap()
+            .into_iter()
+            .map(|b| b.to_string())
+            .collect::<Vec<_>>();
+        containers.sort();
+        assert_eq!(
+            vec![
+                "'bright white' bag",
+                "'dark olive' bag",
+                "'dark orange' bag",
+                "'light red' bag",
+                "'muted yellow' bag",
+                "'shiny gold' bag",
+                "'vibrant plum' bag"
+            ],
+            containers
+        );
+        assert!(graph
+            .containers_of(&Bag::new("light", "red"))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            11,
+            graph.count_inside(&Bag::new("vibrant", "plum")).unwrap()
+        );
+        assert_eq!(0, graph.count_inside(&Bag::new("faded", "blue")).unwrap());
+        assert_eq!(
+            Err(GraphError::UndefinedBag(Bag::new("plain", "grey"))),
+            graph.count_inside(&Bag::new("plain", "grey"))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            GraphError::Cycle(vec![
+                Bag::new("a", "a"),
+                Bag::new("b", "b"),
+                Bag::new("c", "c"),
+                Bag::new("a", "a")
+            ]),
+            BagGraph::parse(
+                "a a bags contain 1 b b bag.\nb b bags contain 2 c c bags.\nc c bags contain 1 a a bag."
+            )
+            .unwrap_err()
+        );
+        assert_eq!(
+            GraphError::Cycle(vec![Bag::new("a", "a"), Bag::new("a", "a")]),
+            BagGraph::parse("a a bags contain 1 a a bag.").unwrap_err()
+        );
+        assert_eq!(
+            GraphError::UndefinedBag(Bag::new("b", "b")),
+            BagGraph::parse("a a bags contain 1 b b bag.").unwrap_err()
+        );
+        assert_eq!(
+            GraphError::DuplicateRule(Bag::new("a", "a")),
+            BagGraph::parse("a a bags contain no other bags.\na a bags contain no other bags.")
+                .unwrap_err()
+        );
+        assert_eq!(
+            GraphError::Parse(2, "b b bags contain lots.".to_string()),
+            BagGraph::parse("a a bags contain no other bags.\nb b bags contain lots.").unwrap_err()
+        );
+        let graph = BagGraph::parse(
+            "a a bags contain 4294967295 b b bags.\nb b bags contain 4294967295 c c bags.\nc c bags contain 4294967295 d d bags.\nd d bags contain no other bags.",
+        )
+        .unwrap();
+        assert_eq!(
+            Err(GraphError::Overflow(Bag::new("a", "a"))),
+            graph.count_inside(&Bag::new("a", "a"))
+        );
+    }
+
+    #[test]
+    fn dot() {
+        let graph =
+            BagGraph::parse(&read_to_string("../../examples/2020/day07/example.txt").unwrap())
+                .unwrap();
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph bags {\n"));
+        assert_eq!(9, dot.matches(";\n").count() - dot.matches("->").count());
+        assert_eq!(13, dot.matches("->").count());
+        assert!(dot.contains("  \"light red\" -> \"muted yellow\" [label=2];\n"));
+
+        let dot = graph.to_dot_around(&Bag::new("dark", "olive")).unwrap();
+        assert!(dot.contains("  \"dark olive\" [style=filled];\n"));
+        assert!(dot.contains("\"shiny gold\" -> \"dark olive\""));
+        assert!(dot.contains("\"dark olive\" -> \"dotted black\""));
+        // a sibling of dark olive, neither inside nor around it
+        assert!(!dot.contains("vibrant plum"));
+        assert!(!dot.contains("\"muted yellow\" -> \"faded blue\""));
+    }
 }
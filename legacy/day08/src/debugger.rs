@@ -0,0 +1,418 @@
+//! A line-based debugger for the handheld console, reading one command per line.
+
+use std::{
+    fmt::{self, Display},
+    io::{self, BufRead, Write},
+    str::FromStr,
+};
+
+use crate::{Instruction, Machine};
+
+const HELP: &str = "\
+step [n]              run n instructions (default 1)
+continue              run until a breakpoint or the program ends
+break <pc>            stop before running the instruction at pc
+break acc <op> <n>    stop when acc changes so that acc <op> n holds, op is one of
+                      == != < <= > >=
+delete <i>            remove breakpoint i
+breakpoints           list breakpoints
+history [n]           show the last n executed instructions (default 10)
+patch <pc> <instr>    replace an instruction, e.g. `patch 7 nop -4`
+print                 show pc, acc and the next instruction
+reset                 start over from pc 0, keeping patches
+quit";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    fn holds(self, left: i32, right: i32) -> bool {
+        match self {
+            Comparison::Eq => left == right,
+            Comparison::Ne => left != right,
+            Comparison::Lt => left < right,
+            Comparison::Le => left <= right,
+            Comparison::Gt => left > right,
+            Comparison::Ge => left >= right,
+        }
+    }
+}
+
+impl FromStr for Comparison {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "==" => Comparison::Eq,
+            "!=" => Comparison::Ne,
+            "<" => Comparison::Lt,
+            "<=" => Comparison::Le,
+            ">" => Comparison::Gt,
+            ">=" => Comparison::Ge,
+            _ => return Err(format!("unknown comparison {}", s)),
+        })
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Comparison::Eq => "==",
+            Comparison::Ne => "!=",
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Breakpoint {
+    /// Before the instruction at this pc runs.
+    Pc(usize),
+    /// After an instruction changes the accumulator so that the comparison holds, when it didn't
+    /// before.
+    Acc(Comparison, i32),
+}
+
+impl Breakpoint {
+    /// Whether the last step, which started with `acc_before`, stops here.
+    fn hit(self, acc_before: i32, machine: &Machine) -> bool {
+        match self {
+            Breakpoint::Pc(pc) => machine.pc() == pc,
+            Breakpoint::Acc(cmp, value) => {
+                !cmp.holds(acc_before, value) && cmp.holds(machine.acc(), value)
+            }
+        }
+    }
+}
+
+impl Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Breakpoint::Pc(pc) => write!(f, "pc {}", pc),
+            Breakpoint::Acc(cmp, value) => write!(f, "acc {} {}", cmp, value),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Command {
+    Step(usize),
+    Continue,
+    Break(Breakpoint),
+    Delete(usize),
+    Breakpoints,
+    History(usize),
+    Patch(usize, Instruction),
+    Print,
+    Reset,
+    Help,
+    Quit,
+}
+
+fn number<T: FromStr>(word: Option<&str>, what: &str) -> Result<T, String> {
+    let word = word.ok_or_else(|| format!("missing {}", what))?;
+    word.parse()
+        .map_err(|_| format!("{} is not a valid {}", word, what))
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let name = words.next().unwrap_or("");
+        let command = match name {
+            "s" | "step" => match words.next() {
+                Some(n) => Command::Step(number(Some(n), "count")?),
+                None => Command::Step(1),
+            },
+            "c" | "continue" => Command::Continue,
+            "b" | "break" => match words.next() {
+                Some("acc") => {
+                    let cmp = words.next().ok_or("missing comparison")?.parse()?;
+                    Command::Break(Breakpoint::Acc(cmp, number(words.next(), "value")?))
+                }
+                pc => Command::Break(Breakpoint::Pc(number(pc, "pc")?)),
+            },
+            "d" | "delete" => Command::Delete(number(words.next(), "breakpoint")?),
+            "breakpoints" => Command::Breakpoints,
+            "h" | "history" => match words.next() {
+                Some(n) => Command::History(number(Some(n), "count")?),
+                None => Command::History(10),
+            },
+            "patch" => {
+                let pc = number(words.next(), "pc")?;
+                let instruction = words.collect::<Vec<_>>().join(" ").parse()?;
+                return Ok(Command::Patch(pc, instruction));
+            }
+            "p" | "print" => Command::Print,
+            "reset" => Command::Reset,
+            "help" => Command::Help,
+            "q" | "quit" => Command::Quit,
+            _ => return Err(format!("unknown command {}, try help", name)),
+        };
+        match words.next() {
+            Some(extra) => Err(format!("unexpected {}", extra)),
+            None => Ok(command),
+        }
+    }
+}
+
+/// A machine with breakpoints, driven by `Command`s.
+pub struct Debugger {
+    machine: Machine,
+    breakpoints: Vec<Breakpoint>,
+}
+
+impl Debugger {
+    pub fn new(program: Vec<Instruction>) -> Debugger {
+        Debugger {
+            machine: Machine::new(program).with_trace(),
+            breakpoints: Vec::new(),
+        }
+    }
+
+    pub fn machine(&self) -> &Machine {
+        &self.machine
+    }
+
+    fn location(&self) -> String {
+        match self.machine.program().get(self.machine.pc()) {
+            Some(instruction) => format!(
+                "pc {}: {}, acc {}",
+                self.machine.pc(),
+                instruction,
+                self.machine.acc()
+            ),
+            None => format!("pc {}: end, acc {}", self.machine.pc(), self.machine.acc()),
+        }
+    }
+
+    /// Runs `command`, writing what happened to `out`. Returns false once asked to quit.
+    pub fn execute<W: Write>(&mut self, command: Command, out: &mut W) -> io::Result<bool> {
+        match command {
+            Command::Step(n) => {
+                for _ in 0..n {
+                    if let Err(termination) = self.machine.step() {
+                        writeln!(out, "{}", termination)?;
+                        return Ok(true);
+                    }
+                }
+                writeln!(out, "{}", self.location())?;
+            }
+            Command::Continue => {
+                let (machine, breakpoints) = (&mut self.machine, &self.breakpoints);
+                let (mut acc, mut hit) = (machine.acc(), None);
+                // always move, or continuing from a breakpoint would stop right there again
+                let result = machine.step().and_then(|()| {
+                    machine.run_until(|m| {
+                        hit = breakpoints.iter().position(|b| b.hit(acc, m));
+                        acc = m.acc();
+                        hit.is_some()
+                    })
+                });
+                match result {
+                    Ok(()) => {
+                        let i = hit.unwrap();
+                        let breakpoint = self.breakpoints[i];
+                        writeln!(
+                            out,
+                            "breakpoint {} ({}) at {}",
+                            i,
+                            breakpoint,
+                            self.location()
+                        )?;
+                    }
+                    Err(termination) => writeln!(out, "{}", termination)?,
+                }
+            }
+            Command::Break(breakpoint) => {
+                self.breakpoints.push(breakpoint);
+                writeln!(
+                    out,
+                    "breakpoint {}: {}",
+                    self.breakpoints.len() - 1,
+                    breakpoint
+                )?;
+            }
+            Command::Delete(i) if i < self.breakpoints.len() => {
+                let breakpoint = self.breakpoints.remove(i);
+                writeln!(out, "deleted breakpoint {}: {}", i, breakpoint)?;
+            }
+            Command::Delete(i) => writeln!(out, "error: no breakpoint {}", i)?,
+            Command::Breakpoints => {
+                for (i, breakpoint) in self.breakpoints.iter().enumerate() {
+                    writeln!(out, "breakpoint {}: {}", i, breakpoint)?;
+                }
+            }
+            Command::History(n) => {
+                let trace = self.machine.trace().unwrap();
+                for entry in &trace[trace.len().saturating_sub(n)..] {
+                    writeln!(
+                        out,
+                        "pc {}: {}, acc {}",
+                        entry.pc, entry.instruction, entry.acc
+                    )?;
+                }
+            }
+            Command::Patch(pc, instruction) => match self.machine.patch(pc, instruction) {
+                Some(old) => writeln!(out, "patched pc {}: {} -> {}", pc, old, instruction)?,
+                None => writeln!(out, "error: no instruction at pc {}", pc)?,
+            },
+            Command::Print => writeln!(out, "{}", self.location())?,
+            Command::Reset => {
+                self.machine.reset();
+                writeln!(out, "{}", self.location())?;
+            }
+            Command::Help => writeln!(out, "{}", HELP)?,
+            Command::Quit => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Reads commands from `input` until it ends or says quit, reporting bad ones to `out` and
+    /// carrying on. Blank lines are skipped.
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, out: &mut W) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match line.parse() {
+                Ok(command) => {
+                    if !self.execute(command, out)? {
+                        break;
+                    }
+                }
+                Err(e) => writeln!(out, "error: {}", e)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Breakpoint, Command, Comparison, Debugger};
+    use crate::{parse_program, Instruction};
+    use std::fs::read_to_string;
+
+    fn session(script: &str) -> String {
+        let program =
+            parse_program(&read_to_string("../../examples/2020/day08/example.txt").unwrap())
+                .unwrap();
+        let mut out = Vec::new();
+        Debugger::new(program)
+            .run(script.as_bytes(), &mut out)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(Ok(Command::Step(1)), "step".parse());
+        assert_eq!(Ok(Command::Step(3)), "s 3".parse());
+        assert_eq!(
+            Ok(Command::Break(Breakpoint::Acc(Comparison::Ge, -2))),
+            "break acc >= -2".parse()
+        );
+        assert_eq!(Ok(Command::Break(Breakpoint::Pc(4))), "b 4".parse());
+        assert_eq!(
+            Ok(Command::Patch(7, Instruction::nop(-4))),
+            "patch 7 nop -4".parse()
+        );
+        assert!("step x".parse::<Command>().is_err());
+        assert!("break acc ~ 1".parse::<Command>().is_err());
+        assert!("patch 7 mul +2".parse::<Command>().is_err());
+        assert!("print now".parse::<Command>().is_err());
+    }
+
+    #[test]
+    fn step_and_history() {
+        let out = session("step 2\nprint\nhistory 1\ncontinue\nhistory\nstep");
+        assert_eq!(
+            "pc 2: jmp +4, acc 1
+pc 2: jmp +4, acc 1
+pc 1: acc +1, acc 1
+infinite loop at pc 1, acc 5
+pc 0: nop +0, acc 0
+pc 1: acc +1, acc 1
+pc 2: jmp +4, acc 1
+pc 6: acc +1, acc 2
+pc 7: jmp -4, acc 2
+pc 3: acc +3, acc 5
+pc 4: jmp -3, acc 5
+infinite loop at pc 1, acc 5
+",
+            out
+        );
+    }
+
+    #[test]
+    fn breakpoints() {
+        let out = session(
+            "b 3\nbreak acc > 4\ncontinue\ncontinue\ndelete 0\nbreakpoints\ncontinue\nd 3\nquit\nstep",
+        );
+        assert_eq!(
+            "breakpoint 0: pc 3
+breakpoint 1: acc > 4
+breakpoint 0 (pc 3) at pc 3: acc +3, acc 2
+breakpoint 1 (acc > 4) at pc 4: jmp -3, acc 5
+deleted breakpoint 0: pc 3
+breakpoint 0: acc > 4
+infinite loop at pc 1, acc 5
+error: no breakpoint 3
+",
+            out
+        );
+    }
+
+    #[test]
+    fn acc_breakpoints_fire_on_change() {
+        // acc stays 1 over the jump after pc 1, which mustn't stop again
+        let out = session(
+            "break acc == 1
+c
+c
+reset
+c",
+        );
+        assert_eq!(
+            "breakpoint 0: acc == 1
+breakpoint 0 (acc == 1) at pc 2: jmp +4, acc 1
+infinite loop at pc 1, acc 5
+pc 0: nop +0, acc 0
+breakpoint 0 (acc == 1) at pc 2: jmp +4, acc 1
+",
+            out
+        );
+    }
+
+    #[test]
+    fn patch_and_resume() {
+        let out = session("b 7\nc\npatch 7 nop -4\nc\nreset\npatch 9 nop +0\nfoo\nc\nc");
+        assert_eq!(
+            "breakpoint 0: pc 7
+breakpoint 0 (pc 7) at pc 7: jmp -4, acc 2
+patched pc 7: jmp -4 -> nop -4
+halted, acc 8
+pc 0: nop +0, acc 0
+error: no instruction at pc 9
+error: unknown command foo, try help
+breakpoint 0 (pc 7) at pc 7: nop -4, acc 2
+halted, acc 8
+",
+            out
+        );
+    }
+}
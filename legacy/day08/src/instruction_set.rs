@@ -0,0 +1,458 @@
+//! Opcodes the console understands, looked up by mnemonic so puzzles can bring their own.
+
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use nom::{
+    branch::alt,
+    character::complete::{alpha1, char, digit1, one_of, space1},
+    combinator::{all_consuming, map_res, recognize},
+    multi::many0,
+    sequence::{pair, preceded, tuple},
+    IResult,
+};
+
+pub const REGISTERS: usize = 8;
+pub const MAX_ARITY: usize = 3;
+
+/// One of the `REGISTERS` registers, so indexing the register bank can't fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Register(usize);
+
+impl Register {
+    /// `r0`.
+    pub const ACC: Register = Register(0);
+
+    /// `None` past the last register.
+    pub fn new(index: usize) -> Option<Register> {
+        if index < REGISTERS {
+            Some(Register(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// A literal with an explicit sign like `+3`, or a register `r0` to `r7`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operand {
+    Value(i32),
+    Register(Register),
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Value(v) => write!(f, "{:+}", v),
+            Operand::Register(r) => write!(f, "r{}", r.0),
+        }
+    }
+}
+
+/// What an opcode accepts in one operand slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OperandKind {
+    Value,
+    Register,
+    Any,
+}
+
+impl OperandKind {
+    fn accepts(self, operand: Operand) -> bool {
+        matches!(
+            (self, operand),
+            (OperandKind::Any, _)
+                | (OperandKind::Value, Operand::Value(_))
+                | (OperandKind::Register, Operand::Register(_))
+        )
+    }
+}
+
+impl Display for OperandKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OperandKind::Value => "a value",
+            OperandKind::Register => "a register",
+            OperandKind::Any => "a value or a register",
+        })
+    }
+}
+
+/// The register bank, `r0` doubling as the accumulator.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Registers([i32; REGISTERS]);
+
+impl Registers {
+    pub fn acc(&self) -> i32 {
+        self.0[0]
+    }
+
+    pub fn get(&self, operand: Operand) -> i32 {
+        match operand {
+            Operand::Value(v) => v,
+            Operand::Register(r) => self.0[r.0],
+        }
+    }
+
+    pub fn set(&mut self, operand: Operand, value: i32) -> Result<(), &'static str> {
+        match operand {
+            Operand::Value(_) => Err("can only write to a register"),
+            Operand::Register(r) => {
+                self.0[r.0] = value;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Runs an instruction's operands against the registers, giving the offset to the next pc.
+pub type Execute = fn(&mut Registers, &[Operand]) -> Result<i64, &'static str>;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Opcode {
+    pub mnemonic: &'static str,
+    /// One entry per operand; instructions with an operand of the wrong kind don't parse.
+    pub operands: &'static [OperandKind],
+    pub execute: Execute,
+}
+
+/// An opcode's mnemonic and its operands, unused slots past the arity being `+0`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    mnemonic: &'static str,
+    arity: usize,
+    operands: [Operand; MAX_ARITY],
+}
+
+impl Instruction {
+    /// Panics with more than `MAX_ARITY` operands.
+    pub fn new(mnemonic: &'static str, operands: &[Operand]) -> Instruction {
+        let mut padded = [Operand::Value(0); MAX_ARITY];
+        padded[..operands.len()].copy_from_slice(operands);
+        Instruction {
+            mnemonic,
+            arity: operands.len(),
+            operands: padded,
+        }
+    }
+
+    pub fn nop(value: i32) -> Instruction {
+        Instruction::new("nop", &[Operand::Value(value)])
+    }
+
+    pub fn acc(value: i32) -> Instruction {
+        Instruction::new("acc", &[Operand::Value(value)])
+    }
+
+    pub fn jmp(offset: i32) -> Instruction {
+        Instruction::new("jmp", &[Operand::Value(offset)])
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        self.mnemonic
+    }
+
+    pub fn operands(&self) -> &[Operand] {
+        &self.operands[..self.arity]
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.mnemonic)?;
+        self.operands()
+            .iter()
+            .try_for_each(|operand| write!(f, " {}", operand))
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = String;
+
+    /// Parses with the default instruction set.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        InstructionSet::default().parse_instruction(s)
+    }
+}
+
+fn parse_operand(input: &str) -> IResult<&str, Operand> {
+    let value = map_res(recognize(pair(one_of("+-"), digit1)), |s: &str| {
+        s.parse().map(Operand::Value)
+    });
+    let register = map_res(preceded(char('r'), digit1), |s: &str| {
+        s.parse()
+            .ok()
+            .and_then(Register::new)
+            .map(Operand::Register)
+            .ok_or(())
+    });
+    alt((value, register))(input)
+}
+
+fn parse_line(input: &str) -> IResult<&str, (&str, Vec<Operand>)> {
+    tuple((alpha1, many0(preceded(space1, parse_operand))))(input)
+}
+
+fn nop(_: &mut Registers, _: &[Operand]) -> Result<i64, &'static str> {
+    Ok(1)
+}
+
+fn acc(registers: &mut Registers, operands: &[Operand]) -> Result<i64, &'static str> {
+    let acc = registers.acc().checked_add(registers.get(operands[0]));
+    registers.set(
+        Operand::Register(Register::ACC),
+        acc.ok_or("accumulator overflow")?,
+    )?;
+    Ok(1)
+}
+
+fn jmp(registers: &mut Registers, operands: &[Operand]) -> Result<i64, &'static str> {
+    Ok(i64::from(registers.get(operands[0])))
+}
+
+/// Opcodes by mnemonic, `Default` being the puzzle's `nop`, `acc` and `jmp`.
+#[derive(Clone, Debug)]
+pub struct InstructionSet {
+    opcodes: Vec<Opcode>,
+}
+
+impl Default for InstructionSet {
+    fn default() -> Self {
+        InstructionSet::empty()
+            .with(Opcode {
+                mnemonic: "nop",
+                operands: &[OperandKind::Value],
+                execute: nop,
+            })
+            .with(Opcode {
+                mnemonic: "acc",
+                operands: &[OperandKind::Value],
+                execute: acc,
+            })
+            .with(Opcode {
+                mnemonic: "jmp",
+                operands: &[OperandKind::Value],
+                execute: jmp,
+            })
+    }
+}
+
+impl InstructionSet {
+    pub fn empty() -> InstructionSet {
+        InstructionSet {
+            opcodes: Vec::new(),
+        }
+    }
+
+    /// Adds `opcode`, replacing any with the same mnemonic.
+    ///
+    /// Panics if it takes more than `MAX_ARITY` operands.
+    pub fn with(mut self, opcode: Opcode) -> InstructionSet {
+        assert!(
+            opcode.operands.len() <= MAX_ARITY,
+            "{} has too many operands",
+            opcode.mnemonic
+        );
+        self.opcodes.retain(|o| o.mnemonic != opcode.mnemonic);
+        self.opcodes.push(opcode);
+        self
+    }
+
+    pub fn get(&self, mnemonic: &str) -> Option<&Opcode> {
+        self.opcodes.iter().find(|o| o.mnemonic == mnemonic)
+    }
+
+    pub fn parse_instruction(&self, s: &str) -> Result<Instruction, String> {
+        let (_, (mnemonic, operands)) =
+            all_consuming(parse_line)(s).map_err(|_| format!("not an instruction: {}", s))?;
+        self.instruction(mnemonic, &operands)
+    }
+
+    /// An instruction for the opcode `mnemonic`, if it takes these operands.
+    pub fn instruction(&self, mnemonic: &str, operands: &[Operand]) -> Result<Instruction, String> {
+        let opcode = self
+            .get(mnemonic)
+            .ok_or_else(|| format!("unknown opcode {}", mnemonic))?;
+        if operands.len() != opcode.operands.len() {
+            return Err(format!(
+                "{} takes {} operands, got {}",
+                mnemonic,
+                opcode.operands.len(),
+                operands.len()
+            ));
+        }
+        for (i, (kind, operand)) in opcode.operands.iter().zip(operands).enumerate() {
+            if !kind.accepts(*operand) {
+                return Err(format!(
+                    "{} takes {} as operand {}, got {}",
+                    mnemonic,
+                    kind,
+                    i + 1,
+                    operand
+                ));
+            }
+        }
+        Ok(Instruction::new(opcode.mnemonic, operands))
+    }
+
+    /// Parses one instruction per line, failing with the 1-based number of the first bad line
+    /// and why it's bad.
+    pub fn parse_program(&self, input: &str) -> Result<Vec<Instruction>, (usize, String)> {
+        input
+            .lines()
+            .enumerate()
+            .map(|(i, l)| self.parse_instruction(l).map_err(|e| (i + 1, e)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InstructionSet, Opcode, Operand, OperandKind, Register, Registers};
+    use crate::{Instruction, Machine, Termination};
+
+    fn mul(registers: &mut Registers, operands: &[Operand]) -> Result<i64, &'static str> {
+        let product = registers
+            .get(operands[0])
+            .checked_mul(registers.get(operands[1]));
+        registers.set(operands[0], product.ok_or("overflow")?)?;
+        Ok(1)
+    }
+
+    fn set(registers: &mut Registers, operands: &[Operand]) -> Result<i64, &'static str> {
+        registers.set(operands[0], registers.get(operands[1]))?;
+        Ok(1)
+    }
+
+    fn add(registers: &mut Registers, operands: &[Operand]) -> Result<i64, &'static str> {
+        let sum = registers.get(operands[0]) + registers.get(operands[1]);
+        registers.set(operands[0], sum)?;
+        Ok(1)
+    }
+
+    /// Jumps by the second operand unless the first is zero.
+    fn jnz(registers: &mut Registers, operands: &[Operand]) -> Result<i64, &'static str> {
+        match registers.get(operands[0]) {
+            0 => Ok(1),
+            _ => Ok(i64::from(registers.get(operands[1]))),
+        }
+    }
+
+    fn extended() -> InstructionSet {
+        InstructionSet::default()
+            .with(Opcode {
+                mnemonic: "mul",
+                operands: &[OperandKind::Register, OperandKind::Any],
+                execute: mul,
+            })
+            .with(Opcode {
+                mnemonic: "set",
+                operands: &[OperandKind::Register, OperandKind::Any],
+                execute: set,
+            })
+            .with(Opcode {
+                mnemonic: "add",
+                operands: &[OperandKind::Register, OperandKind::Any],
+                execute: add,
+            })
+            .with(Opcode {
+                mnemonic: "jnz",
+                operands: &[OperandKind::Any, OperandKind::Any],
+                execute: jnz,
+            })
+    }
+
+    #[test]
+    fn parse() {
+        let set = extended();
+        assert_eq!(
+            Ok(Instruction::new(
+                "mul",
+                &[Operand::Register(Register::ACC), Operand::Value(-3)]
+            )),
+            set.parse_instruction("mul r0 -3")
+        );
+        assert_eq!(
+            "jnz r7 +2",
+            set.parse_instruction("jnz r7 +2").unwrap().to_string()
+        );
+        assert!(set.parse_instruction("mul r0").is_err());
+        assert!(set.parse_instruction("mul r8 +1").is_err());
+        assert!(set.parse_instruction("div r1 +1").is_err());
+        assert_eq!(
+            Err("mul takes a register as operand 1, got +2".to_string()),
+            set.parse_instruction("mul +2 r1")
+        );
+        assert!(InstructionSet::default()
+            .parse_instruction("mul r0 +2")
+            .is_err());
+        assert_eq!(Ok(Instruction::acc(-2)), "acc -2".parse());
+        for instruction in ["nop r1", "acc r0", "jmp r2"].iter() {
+            assert!(
+                instruction.parse::<Instruction>().is_err(),
+                "{}",
+                instruction
+            );
+        }
+        assert_eq!(None, Register::new(8));
+    }
+
+    #[test]
+    fn factorial() {
+        // r0 = 5!, counting r1 down from 5
+        let program = extended()
+            .parse_program("acc +1\nset r1 +5\nmul r0 r1\nadd r1 -1\njnz r1 -2")
+            .unwrap();
+        let machine = Machine::with_instruction_set(program, extended());
+        // the loop body runs again, so loop detection has to stay out of the way
+        assert_eq!(
+            Termination::InfiniteLoop { pc: 2, acc: 5 },
+            machine.clone().run()
+        );
+        assert_eq!(
+            Termination::Halted { acc: 120 },
+            machine.without_loop_detection().run()
+        );
+    }
+
+    #[test]
+    fn faults() {
+        // doesn't parse, but can still be built by hand
+        assert!(extended().parse_instruction("set +1 +2").is_err());
+        let program = vec![Instruction::new(
+            "set",
+            &[Operand::Value(1), Operand::Value(2)],
+        )];
+        assert_eq!(
+            Termination::Fault {
+                pc: 0,
+                reason: "can only write to a register",
+                acc: 0
+            },
+            Machine::with_instruction_set(program.clone(), extended()).run()
+        );
+        assert_eq!(
+            Termination::Fault {
+                pc: 0,
+                reason: "unknown opcode",
+                acc: 0
+            },
+            Machine::new(program).run()
+        );
+        let program = vec![Instruction::acc(i32::MAX), Instruction::acc(1)];
+        assert_eq!(
+            Termination::Fault {
+                pc: 1,
+                reason: "accumulator overflow",
+                acc: i32::MAX
+            },
+            Machine::new(program).run()
+        );
+    }
+}
@@ -1,135 +1,386 @@
 use std::{
-    collections::{HashMap, HashSet},
-    fs::File,
-    io::{self, BufRead},
-    path::Path,
+    fmt::{self, Display},
+    fs::read_to_string,
 };
 
-use nom::{
-    bytes::complete::tag,
-    character::complete::{alpha1, digit1, one_of},
-    combinator::map,
-    sequence::tuple,
-    IResult,
+pub mod debugger;
+mod instruction_set;
+
+pub use instruction_set::{
+    Execute, Instruction, InstructionSet, Opcode, Operand, OperandKind, Register, Registers,
+    MAX_ARITY, REGISTERS,
 };
 
-#[derive(Clone)]
-enum Instruction {
-    Nop(i32),
-    Acc(i32),
-    Jmp(i32),
-}
-
-fn parse_instruction(input: &str) -> IResult<&str, Instruction> {
-    let line = tuple((alpha1, tag(" "), one_of("+-"), digit1));
-    map(line, |(instr, _, sign, num): (&str, &str, char, &str)| {
-        let num = num.parse::<i32>().unwrap();
-        let num = if sign == '-' { -num } else { num };
-        match instr {
-            "nop" => Instruction::Nop(num),
-            "acc" => Instruction::Acc(num),
-            "jmp" => Instruction::Jmp(num),
-            _ => panic!("Unknown instruction {}", instr),
-        }
-    })(input)
+/// Parses one instruction per line, failing with the 1-based number of the first bad line and
+/// why it's bad.
+pub fn parse_program(input: &str) -> Result<Vec<Instruction>, (usize, String)> {
+    InstructionSet::default().parse_program(input)
 }
 
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where
-    P: AsRef<Path>,
-{
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+/// Why a program stopped, with the accumulator at that point.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Termination {
+    /// Ran right past the last instruction.
+    Halted { acc: i32 },
+    /// The instruction at `pc` was about to run a second time.
+    InfiniteLoop { pc: usize, acc: i32 },
+    /// The instruction at `pc` went to `target`, which is neither an instruction nor just past
+    /// the end.
+    OutOfBounds { pc: usize, target: i64, acc: i32 },
+    /// The instruction at `pc` couldn't run, either because the machine's instruction set lacks
+    /// its opcode or because the opcode refused.
+    Fault {
+        pc: usize,
+        reason: &'static str,
+        acc: i32,
+    },
 }
 
-pub fn stop_on_loop(file: &str) -> i32 {
-    let mut visited = HashSet::new();
-    let instructions: HashMap<usize, Instruction> = read_lines(file)
-        .unwrap()
-        .map(Result::unwrap)
-        .map(|l| parse_instruction(&l).unwrap().1)
-        .enumerate()
-        .collect();
-    let mut accumulator = 0;
-    let mut index = 0;
-    while !visited.contains(&index) {
-        visited.insert(index);
-        match instructions.get(&index).unwrap() {
-            Instruction::Nop(_) => index += 1,
-            Instruction::Acc(i) => {
-                accumulator += i;
-                index += 1
-            }
-            Instruction::Jmp(i) => {
-                index = if i.is_negative() {
-                    index.checked_sub((-i) as usize).unwrap()
-                } else {
-                    index.checked_add(*i as usize).unwrap()
-                }
-            }
+impl Termination {
+    pub fn acc(&self) -> i32 {
+        match self {
+            Termination::Halted { acc }
+            | Termination::InfiniteLoop { acc, .. }
+            | Termination::OutOfBounds { acc, .. }
+            | Termination::Fault { acc, .. } => *acc,
         }
     }
-    accumulator
 }
 
-fn halts(instructions: &HashMap<usize, Instruction>) -> Option<i32> {
-    let mut visited = HashSet::new();
-    let mut accumulator = 0;
-    let mut index = 0;
-    while !visited.contains(&index) {
-        visited.insert(index);
-        match instructions.get(&index).unwrap() {
-            Instruction::Nop(_) => index += 1,
-            Instruction::Acc(i) => {
-                accumulator += i;
-                index += 1
+impl Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Termination::Halted { acc } => write!(f, "halted, acc {}", acc),
+            Termination::InfiniteLoop { pc, acc } => {
+                write!(f, "infinite loop at pc {}, acc {}", pc, acc)
             }
-            Instruction::Jmp(i) => {
-                index = if i.is_negative() {
-                    index.checked_sub((-i) as usize).unwrap()
-                } else {
-                    index.checked_add(*i as usize).unwrap()
-                }
+            Termination::OutOfBounds { pc, target, acc } => {
+                write!(
+                    f,
+                    "jump at pc {} out of bounds to {}, acc {}",
+                    pc, target, acc
+                )
+            }
+            Termination::Fault { pc, reason, acc } => {
+                write!(f, "fault at pc {}: {}, acc {}", pc, reason, acc)
             }
         }
-        if index == instructions.len() {
-            return Some(accumulator);
+    }
+}
+
+/// One executed instruction, `acc` being the accumulator after it ran.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TraceEntry {
+    pub pc: usize,
+    pub instruction: Instruction,
+    pub acc: i32,
+}
+
+/// The handheld console: a program, its program counter and the registers.
+#[derive(Clone, Debug)]
+pub struct Machine {
+    program: Vec<Instruction>,
+    instruction_set: InstructionSet,
+    pc: usize,
+    registers: Registers,
+    visited: Vec<bool>,
+    detect_loops: bool,
+    trace: Option<Vec<TraceEntry>>,
+}
+
+impl Machine {
+    /// A machine for the default instruction set.
+    pub fn new(program: Vec<Instruction>) -> Machine {
+        Machine::with_instruction_set(program, InstructionSet::default())
+    }
+
+    pub fn with_instruction_set(
+        program: Vec<Instruction>,
+        instruction_set: InstructionSet,
+    ) -> Machine {
+        Machine {
+            visited: vec![false; program.len()],
+            program,
+            instruction_set,
+            pc: 0,
+            registers: Registers::default(),
+            detect_loops: true,
+            trace: None,
         }
     }
-    None
+
+    /// Lets instructions run more than once, for programs that loop on purpose. `run` may then
+    /// never return.
+    pub fn without_loop_detection(mut self) -> Machine {
+        self.detect_loops = false;
+        self
+    }
+
+    /// Records every executed instruction, see `trace`.
+    pub fn with_trace(mut self) -> Machine {
+        self.trace = Some(Vec::new());
+        self
+    }
+
+    pub fn program(&self) -> &[Instruction] {
+        &self.program
+    }
+
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    pub fn acc(&self) -> i32 {
+        self.registers.acc()
+    }
+
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    pub fn trace(&self) -> Option<&[TraceEntry]> {
+        self.trace.as_deref()
+    }
+
+    /// Replaces the instruction at `index`, returning the old one.
+    ///
+    /// Forgets which instructions already ran, so loop detection starts over with the patched
+    /// program.
+    pub fn patch(&mut self, index: usize, instruction: Instruction) -> Option<Instruction> {
+        let old = std::mem::replace(self.program.get_mut(index)?, instruction);
+        self.visited.iter_mut().for_each(|v| *v = false);
+        Some(old)
+    }
+
+    /// Starts over at pc 0 with a zero accumulator, keeping any patches.
+    pub fn reset(&mut self) {
+        self.pc = 0;
+        self.registers = Registers::default();
+        self.visited.iter_mut().for_each(|v| *v = false);
+        if let Some(trace) = &mut self.trace {
+            trace.clear();
+        }
+    }
+
+    /// Executes the instruction at `pc`, or says why it can't.
+    ///
+    /// A failed step leaves the machine unchanged.
+    pub fn step(&mut self) -> Result<(), Termination> {
+        let (pc, acc) = (self.pc, self.acc());
+        let instruction = match self.program.get(pc) {
+            Some(instruction) => *instruction,
+            None => return Err(Termination::Halted { acc }),
+        };
+        if self.detect_loops && self.visited[pc] {
+            return Err(Termination::InfiniteLoop { pc, acc });
+        }
+        let fault = |reason| Termination::Fault { pc, reason, acc };
+        let opcode = self
+            .instruction_set
+            .get(instruction.mnemonic())
+            .ok_or_else(|| fault("unknown opcode"))?;
+        // run on a copy, so the machine only changes once the step is known to succeed
+        let mut registers = self.registers;
+        let offset = (opcode.execute)(&mut registers, instruction.operands()).map_err(fault)?;
+        let next = pc as i64 + offset;
+        if next < 0 || next > self.program.len() as i64 {
+            return Err(Termination::OutOfBounds {
+                pc,
+                target: next,
+                acc,
+            });
+        }
+        self.registers = registers;
+        self.visited[pc] = true;
+        if let Some(trace) = &mut self.trace {
+            trace.push(TraceEntry {
+                pc,
+                instruction,
+                acc: registers.acc(),
+            });
+        }
+        self.pc = next as usize;
+        Ok(())
+    }
+
+    /// Steps until `stop` returns true for the machine (`Ok`) or it terminates (`Err`).
+    pub fn run_until(&mut self, mut stop: impl FnMut(&Machine) -> bool) -> Result<(), Termination> {
+        while !stop(self) {
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    pub fn run(&mut self) -> Termination {
+        self.run_until(|_| false).unwrap_err()
+    }
 }
 
-pub fn try_until_halts(file: &str) -> i32 {
-    let instructions: HashMap<usize, Instruction> = read_lines(file)
+fn read_program(file: &str) -> Vec<Instruction> {
+    parse_program(&read_to_string(file).unwrap()).unwrap()
+}
+
+pub fn stop_on_loop(file: &str) -> i32 {
+    Machine::new(read_program(file)).run().acc()
+}
+
+/// A `nop`/`jmp` swap that makes the program halt.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Patch {
+    pub index: usize,
+    pub patched: Instruction,
+    /// Accumulator when the patched program halts.
+    pub acc: i32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RepairError {
+    AlreadyHalts {
+        acc: i32,
+    },
+    /// How the unpatched program ends, when no single swap helps.
+    NoSingleFlip(Termination),
+    /// Indices of every instruction whose swap would work.
+    Ambiguous(Vec<usize>),
+    /// The only swap that reaches the end, at `index`, faults on the way there.
+    Faulted {
+        index: usize,
+        reason: &'static str,
+    },
+}
+
+/// The puzzle's repair: `nop` and `jmp` turn into each other, keeping the operand.
+fn flip(instruction_set: &InstructionSet, instruction: Instruction) -> Option<Instruction> {
+    let swapped = match instruction.mnemonic() {
+        "nop" => "jmp",
+        "jmp" => "nop",
+        _ => return None,
+    };
+    instruction_set
+        .instruction(swapped, instruction.operands())
+        .ok()
+}
+
+/// Where `instruction` at `pc` continues, `None` if that's outside the program and not its end,
+/// or not known without running it.
+///
+/// Runs the opcode on scratch registers, which only tells where it goes when that doesn't depend
+/// on the registers: the operands have to be values, and the opcode mustn't branch on the
+/// accumulator, which none of the default ones do.
+fn successor(
+    instruction_set: &InstructionSet,
+    pc: usize,
+    instruction: Instruction,
+    len: usize,
+) -> Option<usize> {
+    if instruction
+        .operands()
+        .iter()
+        .any(|o| matches!(o, Operand::Register(_)))
+    {
+        return None;
+    }
+    let opcode = instruction_set.get(instruction.mnemonic())?;
+    let offset = (opcode.execute)(&mut Registers::default(), instruction.operands()).ok()?;
+    let next = pc as i64 + offset;
+    if (0..=len as i64).contains(&next) {
+        Some(next as usize)
+    } else {
+        None
+    }
+}
+
+/// For every pc, and the end at index `program.len()`, whether the unpatched program halts from
+/// there; found by walking the successor edges backwards from the end.
+fn reaches_end(instruction_set: &InstructionSet, program: &[Instruction]) -> Vec<bool> {
+    let mut predecessors = vec![Vec::new(); program.len() + 1];
+    for (pc, instruction) in program.iter().enumerate() {
+        if let Some(next) = successor(instruction_set, pc, *instruction, program.len()) {
+            predecessors[next].push(pc);
+        }
+    }
+    let mut reaches = vec![false; program.len() + 1];
+    let mut stack = vec![program.len()];
+    while let Some(pc) = stack.pop() {
+        if !reaches[pc] {
+            reaches[pc] = true;
+            stack.extend(&predecessors[pc]);
+        }
+    }
+    reaches
+}
+
+/// Finds the single `nop`/`jmp` swap that makes a program for the default instruction set halt,
+/// in O(n).
+///
+/// Only instructions the unpatched program executes can matter. Swapping one of them works
+/// exactly when its new successor halts in the unpatched program: that path can't lead back to
+/// the swapped instruction, since the unpatched program would then halt too.
+pub fn repair(program: &[Instruction]) -> Result<Patch, RepairError> {
+    let mut machine = Machine::new(program.to_vec()).with_trace();
+    let termination = machine.run();
+    if let Termination::Halted { acc } = termination {
+        return Err(RepairError::AlreadyHalts { acc });
+    }
+    let instruction_set = InstructionSet::default();
+    let reaches = reaches_end(&instruction_set, program);
+    // a jump out of the program never makes it into the trace, but it was still executed
+    let faulting = match termination {
+        Termination::OutOfBounds { pc, .. } => Some(pc),
+        _ => None,
+    };
+    let candidates = machine
+        .trace()
         .unwrap()
-        .map(Result::unwrap)
-        .map(|l| parse_instruction(&l).unwrap().1)
-        .enumerate()
-        .collect();
-
-    for i in 0..instructions.len() {
-        let mut changed_instructions = instructions.clone();
-        changed_instructions
-            .entry(i)
-            .and_modify(|instr| match instr {
-                Instruction::Nop(i) => *instr = Instruction::Jmp(*i),
-                Instruction::Jmp(i) => *instr = Instruction::Nop(*i),
-                _ => (),
-            });
-        if let Some(acc) = halts(&changed_instructions) {
-            return acc;
+        .iter()
+        .map(|entry| entry.pc)
+        .chain(faulting)
+        .filter_map(|pc| {
+            let patched = flip(&instruction_set, program[pc])?;
+            let next = successor(&instruction_set, pc, patched, program.len())?;
+            if reaches[next] {
+                Some((pc, patched))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    match candidates.as_slice() {
+        [] => Err(RepairError::NoSingleFlip(termination)),
+        &[(index, patched)] => {
+            let mut program = program.to_vec();
+            program[index] = patched;
+            match Machine::new(program).run() {
+                Termination::Halted { acc } => Ok(Patch {
+                    index,
+                    patched,
+                    acc,
+                }),
+                // the path to the end is worked out without running it, so it can't see faults
+                Termination::Fault { reason, .. } => Err(RepairError::Faulted { index, reason }),
+                other => unreachable!("patch at {} should halt, got {:?}", index, other),
+            }
         }
+        _ => Err(RepairError::Ambiguous(
+            candidates.iter().map(|(index, _)| *index).collect(),
+        )),
     }
-    panic!("Never halts")
+}
+
+pub fn try_until_halts(file: &str) -> i32 {
+    repair(&read_program(file)).unwrap().acc
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{stop_on_loop, try_until_halts};
+    use crate::{
+        flip, parse_program, read_program, repair, stop_on_loop, try_until_halts, Instruction,
+        InstructionSet, Machine, Patch, RepairError, Termination, TraceEntry,
+    };
 
     #[test]
     fn part_2_example() {
-        assert_eq!(8, try_until_halts("example.txt"));
+        assert_eq!(8, try_until_halts("../../examples/2020/day08/example.txt"));
     }
     #[test]
     fn part_2() {
@@ -138,10 +389,199 @@ mod tests {
 
     #[test]
     fn part_1_example() {
-        assert_eq!(5, stop_on_loop("example.txt"));
+        assert_eq!(5, stop_on_loop("../../examples/2020/day08/example.txt"));
     }
     #[test]
     fn part_1() {
         assert_eq!(2025, stop_on_loop("input.txt"));
     }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            Ok(vec![
+                Instruction::nop(0),
+                Instruction::acc(-99),
+                Instruction::jmp(4)
+            ]),
+            parse_program("nop +0\nacc -99\njmp +4")
+        );
+        assert_eq!(
+            Err((2, "unknown opcode mul".to_string())),
+            parse_program("nop +0\nmul +3")
+        );
+        assert_eq!(
+            Err((1, "not an instruction: acc 3".to_string())),
+            parse_program("acc 3")
+        );
+        assert_eq!(
+            Err((1, "jmp takes a value as operand 1, got r0".to_string())),
+            parse_program("jmp r0")
+        );
+        assert_eq!("jmp -3", Instruction::jmp(-3).to_string());
+        assert_eq!("nop +0", Instruction::nop(0).to_string());
+    }
+
+    #[test]
+    fn terminations() {
+        let program = parse_program("acc +1\njmp +2\nacc +5\nacc +2").unwrap();
+        assert_eq!(Termination::Halted { acc: 3 }, Machine::new(program).run());
+
+        let program = parse_program("acc +1\njmp -1").unwrap();
+        assert_eq!(
+            Termination::InfiniteLoop { pc: 0, acc: 1 },
+            Machine::new(program).run()
+        );
+
+        let program = parse_program("acc +1\njmp -2").unwrap();
+        assert_eq!(
+            Termination::OutOfBounds {
+                pc: 1,
+                target: -1,
+                acc: 1
+            },
+            Machine::new(program).run()
+        );
+        let program = parse_program("jmp +3\nnop +0").unwrap();
+        assert_eq!(
+            Termination::OutOfBounds {
+                pc: 0,
+                target: 3,
+                acc: 0
+            },
+            Machine::new(program).run()
+        );
+        assert_eq!(Termination::Halted { acc: 0 }, Machine::new(vec![]).run());
+    }
+
+    #[test]
+    fn step_and_trace() {
+        let program = parse_program(
+            "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6",
+        )
+        .unwrap();
+        let mut machine = Machine::new(program).with_trace();
+        assert_eq!(Ok(()), machine.step());
+        assert_eq!((1, 0), (machine.pc(), machine.acc()));
+        assert_eq!(Ok(()), machine.run_until(|m| m.pc() == 6));
+        assert_eq!((6, 1), (machine.pc(), machine.acc()));
+        assert_eq!(
+            Err(Termination::InfiniteLoop { pc: 1, acc: 5 }),
+            machine.run_until(|m| m.acc() > 100)
+        );
+        assert_eq!(
+            vec![
+                TraceEntry {
+                    pc: 0,
+                    instruction: Instruction::nop(0),
+                    acc: 0
+                },
+                TraceEntry {
+                    pc: 1,
+                    instruction: Instruction::acc(1),
+                    acc: 1
+                },
+                TraceEntry {
+                    pc: 2,
+                    instruction: Instruction::jmp(4),
+                    acc: 1
+                },
+            ],
+            machine.trace().unwrap()[..3]
+        );
+        assert_eq!(7, machine.trace().unwrap().len());
+        assert!(Machine::new(vec![]).trace().is_none());
+    }
+
+    #[test]
+    fn repair_example() {
+        assert_eq!(
+            Ok(Patch {
+                index: 7,
+                patched: Instruction::nop(-4),
+                acc: 8
+            }),
+            repair(&read_program("../../examples/2020/day08/example.txt"))
+        );
+    }
+
+    #[test]
+    fn repair_errors() {
+        let program = parse_program("acc +1\njmp +1").unwrap();
+        assert_eq!(Err(RepairError::AlreadyHalts { acc: 1 }), repair(&program));
+
+        let program = parse_program("acc +1\njmp -1\njmp -2").unwrap();
+        assert_eq!(
+            Err(RepairError::NoSingleFlip(Termination::InfiniteLoop {
+                pc: 0,
+                acc: 1
+            })),
+            repair(&program)
+        );
+
+        // skipping the loop or defusing it both work
+        let program = parse_program("nop +2\njmp +0\nacc +1").unwrap();
+        assert_eq!(Err(RepairError::Ambiguous(vec![0, 1])), repair(&program));
+
+        // the swap leads to the end, through an overflowing accumulator
+        let program = parse_program("jmp +0\nacc +2147483647\nacc +2147483647").unwrap();
+        assert_eq!(
+            Err(RepairError::Faulted {
+                index: 0,
+                reason: "accumulator overflow"
+            }),
+            repair(&program)
+        );
+
+        // the jump leaving the program is the one to swap
+        let program = parse_program("acc +1\njmp +5").unwrap();
+        assert_eq!(
+            Ok(Patch {
+                index: 1,
+                patched: Instruction::nop(5),
+                acc: 1
+            }),
+            repair(&program)
+        );
+    }
+
+    /// Tries every swap, checking `repair` against the obvious quadratic search.
+    #[test]
+    fn repair_matches_brute_force() {
+        let programs = [
+            "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6",
+            "jmp +2\nacc +1\nnop -2\njmp -1\nacc +7",
+            "acc +3\njmp +3\nacc +1\njmp +2\njmp -3\nnop -4\nacc +2",
+            "nop +5\njmp +1\nacc -2\njmp -3",
+            "acc +1\njmp +5",
+            "jmp +0\nacc +2147483647\nacc +2147483647",
+            "nop +3\nacc +2\njmp -7\nacc +1\njmp -3",
+        ];
+        for source in programs.iter() {
+            let program = parse_program(source).unwrap();
+            let fixes = (0..program.len())
+                .filter_map(|i| {
+                    let mut patched = program.clone();
+                    patched[i] = flip(&InstructionSet::default(), program[i])?;
+                    match Machine::new(patched).run() {
+                        Termination::Halted { acc } => Some((i, acc)),
+                        _ => None,
+                    }
+                })
+                .collect::<Vec<_>>();
+            match repair(&program) {
+                Ok(patch) => assert_eq!(vec![(patch.index, patch.acc)], fixes, "{}", source),
+                Err(RepairError::NoSingleFlip(_)) | Err(RepairError::Faulted { .. }) => {
+                    assert!(fixes.is_empty(), "{}", source)
+                }
+                Err(RepairError::Ambiguous(indices)) => assert_eq!(
+                    indices,
+                    fixes.iter().map(|(i, _)| *i).collect::<Vec<_>>(),
+                    "{}",
+                    source
+                ),
+                Err(RepairError::AlreadyHalts { .. }) => {}
+            }
+        }
+    }
 }
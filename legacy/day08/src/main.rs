@@ -0,0 +1,22 @@
+use std::{fs::read_to_string, io};
+
+use day08::{debugger::Debugger, parse_program};
+
+fn main() -> io::Result<()> {
+    let file = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "input.txt".to_string());
+    let program = match parse_program(&read_to_string(&file)?) {
+        Ok(program) => program,
+        Err((line, reason)) => {
+            eprintln!("{}:{}: {}", file, line, reason);
+            std::process::exit(1);
+        }
+    };
+    eprintln!(
+        "loaded {} instructions from {}, type help for commands",
+        program.len(),
+        file
+    );
+    Debugger::new(program).run(io::stdin().lock(), &mut io::stdout())
+}
@@ -1,76 +1,128 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, VecDeque},
     fs::File,
     io::{self, BufRead},
+    ops::Range,
     path::Path,
 };
 
-fn get_unique_pair_sums(list: &[u64]) -> HashSet<u64> {
-    let mut sums = HashSet::new();
-    for x in list {
-        for y in list {
-            if x != y {
-                sums.insert(x + y);
-            }
+/// The last `preamble` numbers, kept as a multiset so a number leaving the window only drops its
+/// own copy.
+#[derive(Clone, Debug)]
+pub struct Window {
+    preamble: usize,
+    numbers: VecDeque<u64>,
+    counts: HashMap<u64, usize>,
+}
+
+impl Window {
+    pub fn new(preamble: usize) -> Window {
+        Window {
+            preamble,
+            numbers: VecDeque::with_capacity(preamble + 1),
+            counts: HashMap::new(),
         }
     }
-    //println!("Sums: {:?}", sums);
-    sums
-}
 
-fn check_number(previous: &[u64], next: u64) -> Option<u64> {
-    //println!("Checking {} with {:?}", next, previous);
-    if !get_unique_pair_sums(previous).contains(&next) {
-        Some(next)
-    } else {
-        None
+    pub fn is_full(&self) -> bool {
+        self.numbers.len() == self.preamble
     }
-}
 
-pub fn get_first_invalid(preamble_size: usize, file: &str) -> u64 {
-    let lines = read_lines(file)
-        .unwrap()
-        .map(|l| l.unwrap().parse::<u64>().unwrap())
-        .collect::<Vec<_>>();
-    for slice in lines.windows(preamble_size + 1) {
-        if let Some(n) = check_number(&slice[..preamble_size], slice[preamble_size]) {
-            return n;
+    /// Whether two numbers with different values in the window add up to `n`, in O(k).
+    pub fn is_sum(&self, n: u64) -> bool {
+        self.counts
+            .keys()
+            .any(|&x| x < n && n - x != x && self.counts.contains_key(&(n - x)))
+    }
+
+    /// Adds `n`, evicting the oldest number once the window is full.
+    pub fn push(&mut self, n: u64) {
+        if self.is_full() {
+            if let Some(old) = self.numbers.pop_front() {
+                let count = self.counts.get_mut(&old).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    self.counts.remove(&old);
+                }
+            }
+        }
+        if self.preamble > 0 {
+            self.numbers.push_back(n);
+            *self.counts.entry(n).or_insert(0) += 1;
         }
     }
-    panic!("All numbers good")
 }
 
-pub fn part2(preamble_size: usize, file: &str) -> u64 {
-    let lines = read_lines(file)
+/// Lazily yields the position and value of every number after the preamble that isn't the sum of
+/// two of the `preamble` numbers before it.
+pub fn invalid(
+    numbers: impl IntoIterator<Item = u64>,
+    preamble: usize,
+) -> impl Iterator<Item = (usize, u64)> {
+    let mut window = Window::new(preamble);
+    numbers.into_iter().enumerate().filter_map(move |(i, n)| {
+        let invalid = window.is_full() && !window.is_sum(n);
+        window.push(n);
+        if invalid {
+            Some((i, n))
+        } else {
+            None
+        }
+    })
+}
+
+fn read_numbers(file: &str) -> Vec<u64> {
+    read_lines(file)
         .unwrap()
         .map(|l| l.unwrap().parse::<u64>().unwrap())
-        .collect::<Vec<_>>();
-
-    let invalid = lines
-        .windows(preamble_size + 1)
-        .find_map(|slice| check_number(&slice[..preamble_size], slice[preamble_size]))
-        .unwrap();
-    println!("Looking for sums equal to {}", invalid);
-
-    'index: for index in 0..lines.len() {
-        'end: for end in index + 1..lines.len() {
-            let numbers = lines[index..end].iter();
-            let sum: u64 = numbers.clone().copied().sum();
-            match sum.cmp(&invalid) {
-                std::cmp::Ordering::Less => {
-                    continue 'end;
-                }
-                std::cmp::Ordering::Greater => {
-                    continue 'index;
-                }
-                std::cmp::Ordering::Equal => {
-                    return numbers.clone().min().unwrap() + numbers.clone().max().unwrap()
-                }
-            }
+        .collect()
+}
+
+pub fn get_first_invalid(preamble_size: usize, file: &str) -> Option<u64> {
+    invalid(read_numbers(file), preamble_size)
+        .next()
+        .map(|(_, n)| n)
+}
+
+/// A contiguous run of numbers, with its smallest and largest.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Run {
+    pub range: Range<usize>,
+    pub min: u64,
+    pub max: u64,
+}
+
+/// Finds the run of at least two numbers adding up to `target` that ends first, in O(n).
+///
+/// Keeps the window starting as early as possible while its sum stays at most `target`, which
+/// works because the numbers are never negative.
+pub fn contiguous_sum(numbers: &[u64], target: u64) -> Option<Run> {
+    let mut start = 0;
+    // the window sum can exceed u64 right before it shrinks
+    let mut sum = 0u128;
+    for (end, &n) in numbers.iter().enumerate() {
+        sum += u128::from(n);
+        while sum > u128::from(target) {
+            sum -= u128::from(numbers[start]);
+            start += 1;
+        }
+        if sum == u128::from(target) && end > start {
+            let run = &numbers[start..=end];
+            return Some(Run {
+                range: start..end + 1,
+                min: *run.iter().min().unwrap(),
+                max: *run.iter().max().unwrap(),
+            });
         }
     }
+    None
+}
 
-    panic!("Not found")
+pub fn part2(preamble_size: usize, file: &str) -> Option<u64> {
+    let numbers = read_numbers(file);
+    let (_, target) = invalid(numbers.iter().copied(), preamble_size).next()?;
+    let run = contiguous_sum(&numbers, target)?;
+    Some(run.min + run.max)
 }
 
 fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
@@ -83,22 +135,124 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::{get_first_invalid, part2};
+    use crate::{contiguous_sum, get_first_invalid, invalid, part2, read_numbers, Run, Window};
+    use std::collections::HashSet;
+
+    fn get_unique_pair_sums(list: &[u64]) -> HashSet<u64> {
+        let mut sums = HashSet::new();
+        for x in list {
+            for y in list {
+                if x != y {
+                    sums.insert(x + y);
+                }
+            }
+        }
+        sums
+    }
 
     #[test]
     fn part_1_example() {
-        assert_eq!(127, get_first_invalid(5, "example.txt"));
+        assert_eq!(
+            Some(127),
+            get_first_invalid(5, "../../examples/2020/day09/example.txt")
+        );
     }
     #[test]
     fn part_1() {
-        assert_eq!(756008079, get_first_invalid(25, "input.txt"));
+        assert_eq!(Some(756008079), get_first_invalid(25, "input.txt"));
     }
     #[test]
     fn part_2_example() {
-        assert_eq!(62, part2(5, "example.txt"));
+        assert_eq!(Some(62), part2(5, "../../examples/2020/day09/example.txt"));
     }
     #[test]
     fn part_2_input() {
-        assert_eq!(93727241, part2(25, "input.txt"));
+        assert_eq!(Some(93727241), part2(25, "input.txt"));
+    }
+
+    #[test]
+    fn every_invalid() {
+        let numbers = read_numbers("input.txt");
+        let expected = (25..numbers.len())
+            .filter(|&i| !get_unique_pair_sums(&numbers[i - 25..i]).contains(&numbers[i]))
+            .map(|i| (i, numbers[i]))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, invalid(numbers, 25).collect::<Vec<_>>());
+        assert_eq!(
+            vec![(14, 127)],
+            invalid(read_numbers("../../examples/2020/day09/example.txt"), 5).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn window() {
+        let mut window = Window::new(3);
+        for n in [5, 5, 1].iter() {
+            assert!(!window.is_full());
+            window.push(*n);
+        }
+        // the two numbers must have different values
+        assert!(!window.is_sum(10));
+        assert!(window.is_sum(6));
+        window.push(2);
+        // one 5 left the window, the other is still there
+        assert!(window.is_sum(7) && window.is_sum(6) && !window.is_sum(10));
+        window.push(2);
+        assert!(!window.is_sum(6) && window.is_sum(3));
+    }
+
+    #[test]
+    fn preamble() {
+        assert_eq!(
+            vec![(2, 5), (4, 1)],
+            invalid(vec![1, 2, 5, 7, 1].into_iter(), 2).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(0, 1), (1, 2)],
+            invalid(vec![1, 2], 0).collect::<Vec<_>>()
+        );
+        assert!(invalid(vec![1, 2], 5).next().is_none());
+    }
+
+    #[test]
+    fn contiguous() {
+        let numbers = read_numbers("../../examples/2020/day09/example.txt");
+        assert_eq!(
+            Some(Run {
+                range: 2..6,
+                min: 15,
+                max: 47
+            }),
+            contiguous_sum(&numbers, 127)
+        );
+        // a single number isn't a run
+        assert_eq!(None, contiguous_sum(&[1, 5, 1], 5));
+        assert_eq!(
+            Some(2..4),
+            contiguous_sum(&[3, 5, 0, 1], 1).map(|r| r.range)
+        );
+        assert_eq!(Some(0..2), contiguous_sum(&[0, 0, 3], 0).map(|r| r.range));
+        assert_eq!(None, contiguous_sum(&[], 0));
+        assert_eq!(
+            Some(1..3),
+            contiguous_sum(&[u64::MAX, u64::MAX - 1, 1], u64::MAX).map(|r| r.range)
+        );
+    }
+
+    /// Checks every pair of bounds on a slice of the input.
+    #[test]
+    fn contiguous_matches_brute_force() {
+        let numbers = &read_numbers("input.txt")[..200];
+        for &target in [numbers[40] + numbers[41], 1_000, 12_345, 99_999].iter() {
+            let expected = (2..=numbers.len())
+                .flat_map(|end| (0..end - 1).map(move |start| start..end))
+                .find(|range| numbers[range.clone()].iter().sum::<u64>() == target);
+            assert_eq!(
+                expected,
+                contiguous_sum(numbers, target).map(|r| r.range),
+                "{}",
+                target
+            );
+        }
     }
 }
@@ -0,0 +1,71 @@
+use std::str::Lines;
+
+/// Groups of consecutive non-blank lines, as used by the puzzles with blank-line separated records.
+///
+/// Works with `\n` and `\r\n` line endings, treats lines holding only whitespace as blank,
+/// strips trailing whitespace from every line and does not need a final newline.
+pub fn records(input: &str) -> Records<'_> {
+    Records {
+        lines: input.lines(),
+    }
+}
+
+pub struct Records<'a> {
+    lines: Lines<'a>,
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = Vec<&'a str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record = Vec::new();
+        for line in &mut self.lines {
+            let line = line.trim_end();
+            if !line.is_empty() {
+                record.push(line);
+            } else if !record.is_empty() {
+                break;
+            }
+        }
+        if record.is_empty() {
+            None
+        } else {
+            Some(record)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::records;
+
+    fn collect(input: &str) -> Vec<Vec<&str>> {
+        records(input).collect()
+    }
+
+    #[test]
+    fn line_endings() {
+        let expected = vec![vec!["abc"], vec!["a", "b", "c"], vec!["ab", "ac"]];
+        assert_eq!(expected, collect("abc\n\na\nb\nc\n\nab\nac\n"));
+        assert_eq!(
+            expected,
+            collect("abc\r\n\r\na\r\nb\r\nc\r\n\r\nab\r\nac\r\n")
+        );
+        assert_eq!(expected, collect("abc\n\na\nb\nc\n\nab\nac"));
+        assert_eq!(expected, collect("abc\r\n\r\na\r\nb\r\nc\r\n\r\nab\r\nac"));
+    }
+
+    #[test]
+    fn whitespace() {
+        assert_eq!(
+            vec![vec!["a b", "c"], vec!["d"]],
+            collect("\n \na b \t\nc\n  \t\r\n\n\nd  \n \n")
+        );
+    }
+
+    #[test]
+    fn empty() {
+        assert!(collect("").is_empty());
+        assert!(collect("\n\r\n  \n").is_empty());
+    }
+}
@@ -0,0 +1,51 @@
+//! Plays the day 11 seating simulation back in the terminal and optionally
+//! dumps every generation as an image.
+//!
+//! `cargo run --bin day11_animate -- [--part 2] [--delay 100] [--ppm DIR | --pgm DIR] [--scale 4] [INPUT]`
+use std::{
+    fs::read_to_string,
+    io::stdout,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use advent_of_code::day11::{export_frames, input_generator, play, ImageFormat, Rules};
+use anyhow::{anyhow, bail, Context};
+
+fn main() -> anyhow::Result<()> {
+    let mut rules = Rules::Adjacent;
+    let mut delay = Duration::from_millis(100);
+    let mut export: Option<(ImageFormat, PathBuf)> = None;
+    let mut scale = 4;
+    let mut input = PathBuf::from("input/2020/day11.txt");
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+        match arg.as_str() {
+            "--part" => {
+                rules = match value()?.as_str() {
+                    "1" => Rules::Adjacent,
+                    "2" => Rules::Visible,
+                    p => bail!("unknown part {}", p),
+                }
+            }
+            "--delay" => delay = Duration::from_millis(value()?.parse()?),
+            "--ppm" => export = Some((ImageFormat::Ppm, value()?.into())),
+            "--pgm" => export = Some((ImageFormat::Pgm, value()?.into())),
+            "--scale" => scale = value()?.parse()?,
+            _ => input = arg.into(),
+        }
+    }
+
+    let area = input_generator(
+        &read_to_string(&input).with_context(|| format!("reading {}", input.display()))?,
+    )?;
+    let frames = area.generations(rules)?;
+    play(&frames, delay, &mut stdout())?;
+    if let Some((format, dir)) = export {
+        let written = export_frames(&frames, Path::new(&dir), format, scale)?;
+        println!("wrote {} frames to {}", written.len(), dir.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,44 @@
+//! Writes the day 12 route as an SVG and prints a short summary of the track.
+//!
+//! `cargo run --bin day12_svg -- [--part 2] [--out route.svg] [INPUT]`
+use std::{fs, path::PathBuf};
+
+use advent_of_code::day12::{input_generator, Navigation, Track};
+use anyhow::{anyhow, bail, Context};
+
+fn main() -> anyhow::Result<()> {
+    let mut navigation = Navigation::Heading;
+    let mut out = PathBuf::from("day12.svg");
+    let mut input = PathBuf::from("input/2020/day12.txt");
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+        match arg.as_str() {
+            "--part" => {
+                navigation = match value()?.as_str() {
+                    "1" => Navigation::Heading,
+                    "2" => Navigation::Waypoint,
+                    p => bail!("unknown part {}", p),
+                }
+            }
+            "--out" => out = value()?.into(),
+            _ => input = arg.into(),
+        }
+    }
+
+    let instructions = input_generator(
+        &fs::read_to_string(&input).with_context(|| format!("reading {}", input.display()))?,
+    )?;
+    let track = Track::follow(&instructions, navigation);
+    let (min, max) = track.bounding_box();
+    println!("steps:       {}", instructions.len());
+    println!("end:         {:?}", track.end());
+    println!("distance:    {}", track.end().manhattan_distance());
+    println!("length:      {}", track.length());
+    println!("furthest:    {:?}", track.furthest());
+    println!("bounding box {:?} to {:?}", min, max);
+    fs::write(&out, track.to_svg()).with_context(|| format!("writing {}", out.display()))?;
+    println!("wrote {}", out.display());
+    Ok(())
+}
@@ -0,0 +1,56 @@
+//! Runs the legacy day 5 crate, so its events show up with the rest under `-v`.
+
+use anyhow::{anyhow, Context};
+
+use ::day05::{BoardingPass, SeatMap};
+
+#[aoc_generator(day5)]
+pub fn input_generator(input: &str) -> anyhow::Result<Vec<BoardingPass>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| line.parse().with_context(|| format!("line {}", i + 1)))
+        .collect()
+}
+
+#[aoc(day5, part1)]
+pub fn part1(input: &[BoardingPass]) -> anyhow::Result<u64> {
+    input
+        .iter()
+        .map(BoardingPass::id)
+        .max()
+        .ok_or_else(|| anyhow!("no boarding passes"))
+}
+
+/// The free seat between two taken ones.
+#[aoc(day5, part2)]
+pub fn part2(input: &[BoardingPass]) -> anyhow::Result<u64> {
+    SeatMap::new(input)
+        .single_gaps()
+        .first()
+        .copied()
+        .ok_or_else(|| anyhow!("no free seat"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    #[test]
+    fn part1() {
+        let input = read_to_string("input/2020/day5.txt").expect("input file missing");
+        assert_eq!(
+            822,
+            super::part1(&super::input_generator(&input).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn part2() {
+        let input = read_to_string("input/2020/day5.txt").expect("input file missing");
+        assert_eq!(
+            705,
+            super::part2(&super::input_generator(&input).unwrap()).unwrap()
+        );
+    }
+}
@@ -1,9 +1,18 @@
 use std::{
     convert::{TryFrom, TryInto},
     fmt::Debug,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
 };
 
 use itertools::Itertools;
+use nom::{character::complete::anychar, combinator::map_res};
+use tracing::{debug, info, instrument, trace};
+
+use crate::parsing;
 
 #[derive(PartialEq, Copy, Clone)]
 enum SpaceType {
@@ -68,7 +77,6 @@ impl Area {
         let mut result = Vec::new();
         for r in min_row..=max_row {
             for c in min_col..=max_col {
-                // println!("checking {} {}", r, c);
                 if r != row || c != col {
                     result.push(self.get_pos(r, c));
                 }
@@ -93,26 +101,19 @@ impl Area {
     ) -> anyhow::Result<Option<SpaceType>> {
         let mut row: isize = row.try_into()?;
         let mut col: isize = col.try_into()?;
-        // println!(
-        //     "Checking neighbors of {},{} in dir {},{}",
-        //     row, col, drow, dcol
-        // );
         loop {
             row += drow;
             col += dcol;
-            // print!("looking at {},{}: ", row, col);
             if !(0..self.rows.try_into()?).contains(&row)
                 || !(0..self.cols.try_into()?).contains(&col)
             {
-                // println!("outside");
+                trace!(row, col, drow, dcol, "no seat in sight");
                 return Ok(None);
             }
             if let SpaceType::Seat(occ) = self.get_pos(row.try_into()?, col.try_into()?) {
-                // println!("seat: {}", occ);
+                trace!(row, col, drow, dcol, occupied = *occ, "seat in sight");
                 return Ok(Some(SpaceType::Seat(*occ)));
             }
-
-            // println!("floor");
         }
     }
 
@@ -133,7 +134,7 @@ impl Area {
             .map(|(r, c)| self.get_seat_in_direction(row, col, *r, *c))
             .collect::<anyhow::Result<Vec<_>>>()?
             .into_iter()
-            .filter_map(|x| x)
+            .flatten()
             .filter(|x| *x == SpaceType::Seat(true))
             .collect_vec())
     }
@@ -191,78 +192,212 @@ impl Area {
             .filter(|p| **p == SpaceType::Seat(true))
             .count()
     }
+
+    fn step_with(&self, rules: Rules) -> anyhow::Result<Area> {
+        match rules {
+            Rules::Adjacent => Ok(self.step()),
+            Rules::Visible => self.step2(),
+        }
+    }
+
+    /// Whether both rule sets reach a stable layout within `max_steps` generations. Random layouts
+    /// can also keep flipping between states forever, and then the parts never return.
+    pub(crate) fn settles(&self, max_steps: usize) -> bool {
+        [Rules::Adjacent, Rules::Visible].iter().all(|&rules| {
+            let mut area = self.clone();
+            for _ in 0..max_steps {
+                match area.step_with(rules) {
+                    Ok(next) if next == area => return true,
+                    Ok(next) => area = next,
+                    Err(_) => return false,
+                }
+            }
+            false
+        })
+    }
+
+    /// Every generation starting with `self`, up to and including the first stable one.
+    pub fn generations(&self, rules: Rules) -> anyhow::Result<Vec<Area>> {
+        let mut frames = vec![self.clone()];
+        loop {
+            let next = frames.last().unwrap().step_with(rules)?;
+            if Some(&next) == frames.last() {
+                return Ok(frames);
+            }
+            frames.push(next);
+        }
+    }
+
+    /// Same as `Debug`, but with ANSI colours: floor is grey, empty seats green, occupied seats red.
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::new();
+        for row in self.places.chunks_exact(self.cols) {
+            for s in row {
+                out.push_str(match s {
+                    SpaceType::Floor => "\x1b[90m.",
+                    SpaceType::Seat(false) => "\x1b[32mL",
+                    SpaceType::Seat(true) => "\x1b[31m#",
+                });
+            }
+            out.push_str("\x1b[0m\n");
+        }
+        out
+    }
+
+    /// Writes the area as a binary PPM (`P6`) or PGM (`P5`) image, each place `scale` pixels wide.
+    pub fn write_image<W: Write>(
+        &self,
+        format: ImageFormat,
+        scale: usize,
+        out: &mut W,
+    ) -> io::Result<()> {
+        let (magic, channels) = match format {
+            ImageFormat::Ppm => ("P6", 3),
+            ImageFormat::Pgm => ("P5", 1),
+        };
+        write!(
+            out,
+            "{}\n{} {}\n255\n",
+            magic,
+            self.cols * scale,
+            self.rows * scale
+        )?;
+        for row in self.places.chunks_exact(self.cols) {
+            let line = row
+                .iter()
+                .flat_map(|s| {
+                    let pixel: &[u8] = match (format, s) {
+                        (ImageFormat::Ppm, SpaceType::Floor) => &[40, 40, 40],
+                        (ImageFormat::Ppm, SpaceType::Seat(false)) => &[60, 180, 75],
+                        (ImageFormat::Ppm, SpaceType::Seat(true)) => &[230, 25, 75],
+                        (ImageFormat::Pgm, SpaceType::Floor) => &[0],
+                        (ImageFormat::Pgm, SpaceType::Seat(false)) => &[128],
+                        (ImageFormat::Pgm, SpaceType::Seat(true)) => &[255],
+                    };
+                    pixel.repeat(scale)
+                })
+                .collect_vec();
+            debug_assert_eq!(line.len(), self.cols * scale * channels);
+            for _ in 0..scale {
+                out.write_all(&line)?;
+            }
+        }
+        Ok(())
+    }
 }
 
-#[aoc_generator(day11)]
-pub fn input_generator(input: &str) -> Area {
-    let line_length = input.lines().next().unwrap().len();
-    debug_assert!(input.lines().map(|line| line.len()).all_equal());
-    let places = input
-        .lines()
-        .flat_map(|l| l.chars().map(|c| c.try_into().unwrap()))
-        .collect_vec();
-    Area {
-        cols: line_length,
-        rows: places.len() / line_length,
-        places,
+/// Which neighbours decide whether a seat changes, part 1 or part 2 of the puzzle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Rules {
+    Adjacent,
+    Visible,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageFormat {
+    Ppm,
+    Pgm,
+}
+
+impl ImageFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Pgm => "pgm",
+        }
+    }
+}
+
+/// Plays `frames` back in the terminal, clearing the screen between generations.
+pub fn play<W: Write>(frames: &[Area], delay: Duration, out: &mut W) -> io::Result<()> {
+    for (i, frame) in frames.iter().enumerate() {
+        writeln!(
+            out,
+            "\x1b[2J\x1b[H{}generation {}/{}, {} occupied",
+            frame.to_ansi(),
+            i,
+            frames.len() - 1,
+            frame.get_occupied_count()
+        )?;
+        out.flush()?;
+        if i + 1 < frames.len() {
+            thread::sleep(delay);
+        }
     }
+    Ok(())
+}
+
+/// Writes one image per generation into `dir` as `gen_0000.ppm`, `gen_0001.ppm`, …
+pub fn export_frames(
+    frames: &[Area],
+    dir: &Path,
+    format: ImageFormat,
+    scale: usize,
+) -> io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    frames
+        .iter()
+        .enumerate()
+        .map(|(i, frame)| {
+            let path = dir.join(format!("gen_{:04}.{}", i, format.extension()));
+            let mut out = BufWriter::new(File::create(&path)?);
+            frame.write_image(format, scale, &mut out)?;
+            out.flush()?;
+            Ok(path)
+        })
+        .collect()
+}
+
+#[aoc_generator(day11)]
+pub fn input_generator(input: &str) -> anyhow::Result<Area> {
+    let rows = parsing::parse(input, parsing::grid(map_res(anychar, SpaceType::try_from)))?;
+    Ok(Area {
+        cols: rows[0].len(),
+        rows: rows.len(),
+        places: rows.into_iter().flatten().collect(),
+    })
 }
 
 #[aoc(day11, part1)]
+#[instrument(level = "info", skip_all)]
 pub fn part1(input: &Area) -> usize {
-    // println!("{:?}", input);
+    trace!("initial layout\n{:?}", input);
     let mut prev = input.step();
-    // println!("{:?}", prev);
-    loop {
+    for generation in 1.. {
+        debug!(generation, occupied = prev.get_occupied_count());
+        trace!("layout\n{:?}", prev);
         let next = prev.step();
-        // println!("{:?}", next);
         if next == prev {
-            return next.get_occupied_count();
-        } else {
-            prev = next;
+            info!(generations = generation, "settled");
+            break;
         }
+        prev = next;
     }
+    prev.get_occupied_count()
 }
 
 #[aoc(day11, part2)]
+#[instrument(level = "info", skip_all)]
 pub fn part2(input: &Area) -> anyhow::Result<usize> {
-    // println!("{:?}", input);
+    trace!("initial layout\n{:?}", input);
     let mut prev = input.step2()?;
-    // println!("{:?}", prev);
-    loop {
+    for generation in 1.. {
+        debug!(generation, occupied = prev.get_occupied_count());
+        trace!("layout\n{:?}", prev);
         let next = prev.step2()?;
-        // println!("{:?}", next);
         if next == prev {
-            return Ok(next.get_occupied_count());
-        } else {
-            prev = next;
+            info!(generations = generation, "settled");
+            break;
         }
+        prev = next;
     }
+    Ok(prev.get_occupied_count())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn part1_example() {
-        assert_eq!(
-            37,
-            part1(&input_generator(
-                "\
-L.LL.LL.LL
-LLLLLLL.LL
-L.L.L..L..
-LLLL.LL.LL
-L.LL.LL.LL
-L.LLLLL.LL
-..L.L.....
-LLLLLLLLLL
-L.LLLLLL.L
-L.LLLLL.LL"
-            ))
-        );
-    }
     #[test]
     fn part1_mini() {
         assert_eq!(
@@ -280,6 +415,7 @@ L.LLLLL.LL"
 #.######.#
 #.#####.##"
             )
+            .unwrap()
             .get_occ_neighbors(0, 3)
         );
     }
@@ -300,9 +436,35 @@ L.LLLLL.LL"
 #........
 ...#....."
             )
+            .unwrap()
             .get_occ_cardinal_seats(4, 3)
             .unwrap()
             .len()
         );
     }
+
+    #[test]
+    fn generations_end_stable() {
+        let area = input_generator("L.L\nLLL\nL.L").unwrap();
+        let frames = area.generations(Rules::Adjacent).unwrap();
+        assert_eq!(area, frames[0]);
+        assert_eq!(
+            frames[frames.len() - 1].step(),
+            frames[frames.len() - 1].clone()
+        );
+        assert_eq!(part1(&area), frames.last().unwrap().get_occupied_count());
+    }
+
+    #[test]
+    fn write_image() {
+        let area = input_generator("L.#\n#.L").unwrap();
+        let mut ppm = Vec::new();
+        area.write_image(ImageFormat::Ppm, 2, &mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n6 4\n255\n"));
+        assert_eq!(b"P6\n6 4\n255\n".len() + 6 * 4 * 3, ppm.len());
+
+        let mut pgm = Vec::new();
+        area.write_image(ImageFormat::Pgm, 1, &mut pgm).unwrap();
+        assert_eq!(b"P5\n3 2\n255\n\x80\x00\xff\xff\x00\x80".to_vec(), pgm);
+    }
 }
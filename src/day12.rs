@@ -1,168 +1,354 @@
 use itertools::Itertools;
+use nom::{
+    branch::alt,
+    character::complete::char,
+    combinator::{cut, map, verify},
+    error::context,
+    sequence::preceded,
+};
+use tracing::{info, instrument, trace};
+
+use crate::parsing::{self, unsigned, IResult};
+
+/// A position or displacement, `north` and `east` in map units.
+///
+/// `f64` holds every integer up to 2^53 exactly, so routes that only turn by right angles
+/// give exact answers, while any other heading is still representable.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vector {
+    pub north: f64,
+    pub east: f64,
+}
+
+impl Vector {
+    fn new(north: f64, east: f64) -> Vector {
+        Vector { north, east }
+    }
+
+    fn add(self, other: Vector) -> Vector {
+        Vector::new(self.north + other.north, self.east + other.east)
+    }
+
+    fn scale(self, factor: f64) -> Vector {
+        Vector::new(self.north * factor, self.east * factor)
+    }
+
+    /// Rotates counter-clockwise (to the left) by `degrees`, exactly for multiples of 90.
+    fn rotate(self, degrees: i32) -> Vector {
+        match degrees.rem_euclid(360) {
+            0 => self,
+            90 => Vector::new(self.east, -self.north),
+            180 => Vector::new(-self.north, -self.east),
+            270 => Vector::new(-self.east, self.north),
+            d => {
+                let (sin, cos) = f64::from(d).to_radians().sin_cos();
+                Vector::new(
+                    self.north * cos + self.east * sin,
+                    self.east * cos - self.north * sin,
+                )
+            }
+        }
+    }
+
+    fn sub(self, other: Vector) -> Vector {
+        Vector::new(self.north - other.north, self.east - other.east)
+    }
+
+    fn length(self) -> f64 {
+        self.north.hypot(self.east)
+    }
+
+    pub fn manhattan_distance(self) -> f64 {
+        self.north.abs() + self.east.abs()
+    }
+}
+
+/// One navigation instruction, `Left` and `Right` in degrees.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    North(i32),
+    South(i32),
+    East(i32),
+    West(i32),
+    Left(i32),
+    Right(i32),
+    Forward(i32),
+}
 
 #[derive(Clone, Debug)]
 struct Ship {
-    north: i16,
-    east: i16,
-    angle: i16,
-    wnorth: i16,
-    weast: i16,
+    position: Vector,
+    /// Unit vector the ship is facing.
+    heading: Vector,
+    /// Relative to the ship.
+    waypoint: Vector,
 }
 
 impl Default for Ship {
     fn default() -> Self {
         Ship {
-            north: 0,
-            east: 0,
-            angle: 0,
-            wnorth: 1,
-            weast: 10,
+            position: Vector::new(0.0, 0.0),
+            heading: Vector::new(0.0, 1.0),
+            waypoint: Vector::new(1.0, 10.0),
         }
     }
 }
 
 impl Ship {
-    fn move_ship(&self, north: i16, east: i16) -> Ship {
+    fn move_ship(&self, north: i32, east: i32) -> Ship {
         Ship {
-            north: self.north + north,
-            east: self.east + east,
-            angle: self.angle,
+            position: self
+                .position
+                .add(Vector::new(f64::from(north), f64::from(east))),
             ..self.clone()
         }
     }
 
-    fn turn_ship(&self, angle: i16) -> Ship {
+    fn turn_ship(&self, angle: i32) -> Ship {
         Ship {
-            north: self.north,
-            east: self.east,
-            angle: {
-                let mut angle = self.angle + angle;
-                while angle < 0 {
-                    angle += 360;
-                }
-                while angle >= 360 {
-                    angle -= 360;
-                }
-                angle
-            },
+            heading: self.heading.rotate(angle),
             ..self.clone()
         }
     }
 
-    fn forward_ship(&self, distance: i16) -> Ship {
-        let mut ship = self.clone();
-        match self.angle {
-            0 => ship.east += distance,
-            90 => ship.north += distance,
-            180 => ship.east -= distance,
-            270 => ship.north -= distance,
-            _ => unreachable!(),
+    fn forward_ship(&self, distance: i32) -> Ship {
+        Ship {
+            position: self.position.add(self.heading.scale(f64::from(distance))),
+            ..self.clone()
         }
-        ship
     }
 
-    fn process(&self, (instr, i): (char, i16)) -> Ship {
-        match instr {
-            'N' => self.move_ship(i, 0),
-            'S' => self.move_ship(-i, 0),
-            'E' => self.move_ship(0, i),
-            'W' => self.move_ship(0, -i),
-            'L' => self.turn_ship(i),
-            'R' => self.turn_ship(-i),
-            'F' => self.forward_ship(i),
-            _ => unreachable!(),
+    fn process(&self, action: Action) -> Ship {
+        match action {
+            Action::North(i) => self.move_ship(i, 0),
+            Action::South(i) => self.move_ship(-i, 0),
+            Action::East(i) => self.move_ship(0, i),
+            Action::West(i) => self.move_ship(0, -i),
+            Action::Left(i) => self.turn_ship(i),
+            Action::Right(i) => self.turn_ship(-i),
+            Action::Forward(i) => self.forward_ship(i),
         }
     }
 
-    fn move_waypoint(&self, north: i16, east: i16) -> Ship {
+    fn move_waypoint(&self, north: i32, east: i32) -> Ship {
         Ship {
-            wnorth: self.wnorth + north,
-            weast: self.weast + east,
+            waypoint: self
+                .waypoint
+                .add(Vector::new(f64::from(north), f64::from(east))),
             ..self.clone()
         }
     }
 
-    fn turn_waypoint(&self, angle: i16) -> Ship {
-        let mut ship = self.clone();
-        let angle = {
-            let mut angle = self.angle + angle;
-            while angle < 0 {
-                angle += 360;
-            }
-            while angle >= 360 {
-                angle -= 360;
-            }
-            angle
-        };
-        // println!("{}", angle);
-        match angle {
-            0 => (),
-            90 => {
-                ship.weast = -self.wnorth;
-                ship.wnorth = self.weast;
-            }
-            180 => {
-                ship.weast = -self.weast;
-                ship.wnorth = -self.wnorth;
-            }
-            270 => {
-                ship.weast = self.wnorth;
-                ship.wnorth = -self.weast;
-            }
-            _ => unreachable!(),
+    fn turn_waypoint(&self, angle: i32) -> Ship {
+        Ship {
+            waypoint: self.waypoint.rotate(angle),
+            ..self.clone()
         }
-        ship
     }
 
-    fn go_to_waypoint(&self, i: i16) -> Ship {
+    fn go_to_waypoint(&self, i: i32) -> Ship {
         Ship {
-            north: self.north + (self.wnorth * i),
-            east: self.east + (self.weast * i),
+            position: self.position.add(self.waypoint.scale(f64::from(i))),
             ..self.clone()
         }
     }
 
-    fn process2(&self, (instr, i): (char, i16)) -> Ship {
-        match instr {
-            'N' => self.move_waypoint(i, 0),
-            'S' => self.move_waypoint(-i, 0),
-            'E' => self.move_waypoint(0, i),
-            'W' => self.move_waypoint(0, -i),
-            'L' => self.turn_waypoint(i),
-            'R' => self.turn_waypoint(-i),
-            'F' => self.go_to_waypoint(i),
-            _ => unreachable!(),
+    fn process2(&self, action: Action) -> Ship {
+        match action {
+            Action::North(i) => self.move_waypoint(i, 0),
+            Action::South(i) => self.move_waypoint(-i, 0),
+            Action::East(i) => self.move_waypoint(0, i),
+            Action::West(i) => self.move_waypoint(0, -i),
+            Action::Left(i) => self.turn_waypoint(i),
+            Action::Right(i) => self.turn_waypoint(-i),
+            Action::Forward(i) => self.go_to_waypoint(i),
         }
     }
 
-    fn manhattan_distance(&self) -> i16 {
-        self.north.abs() + self.east.abs()
+    /// Rounded to the nearest unit, which is exact when every turn was a right angle.
+    fn manhattan_distance(&self) -> i64 {
+        self.position.manhattan_distance().round() as i64
+    }
+}
+
+/// Whether `F` follows the ship's heading (part 1) or its waypoint (part 2).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Navigation {
+    Heading,
+    Waypoint,
+}
+
+/// Every state of the ship along a route, starting with the initial one.
+#[derive(Clone, Debug)]
+pub struct Track {
+    navigation: Navigation,
+    ships: Vec<Ship>,
+}
+
+impl Track {
+    pub fn follow(input: &[Action], navigation: Navigation) -> Track {
+        let mut ships = vec![Ship::default()];
+        for instr in input {
+            let ship = ships.last().unwrap();
+            let next = match navigation {
+                Navigation::Heading => ship.process(*instr),
+                Navigation::Waypoint => ship.process2(*instr),
+            };
+            ships.push(next);
+        }
+        Track { navigation, ships }
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Vector> + '_ {
+        self.ships.iter().map(|s| s.position)
+    }
+
+    /// Absolute waypoint positions, only meaningful for `Navigation::Waypoint`.
+    pub fn waypoints(&self) -> impl Iterator<Item = Vector> + '_ {
+        self.ships.iter().map(|s| s.position.add(s.waypoint))
+    }
+
+    /// `(south-west corner, north-east corner)` of all ship positions.
+    pub fn bounding_box(&self) -> (Vector, Vector) {
+        bounding_box(self.positions())
+    }
+
+    /// Distance travelled by the ship, summed over straight segments.
+    pub fn length(&self) -> f64 {
+        self.positions()
+            .tuple_windows()
+            .map(|(a, b)| b.sub(a).length())
+            .sum()
     }
+
+    /// The position with the largest Manhattan distance from the origin.
+    pub fn furthest(&self) -> Vector {
+        self.positions()
+            .max_by(|a, b| {
+                a.manhattan_distance()
+                    .partial_cmp(&b.manhattan_distance())
+                    .unwrap()
+            })
+            .unwrap()
+    }
+
+    pub fn end(&self) -> Vector {
+        self.ships.last().unwrap().position
+    }
+
+    /// Renders the route as an SVG polyline with north pointing up.
+    ///
+    /// Waypoint tracks also get a dashed line for the waypoint, the origin is marked green
+    /// and the furthest point red.
+    pub fn to_svg(&self) -> String {
+        /// SVG's y axis points down; adding `0.0` turns `-0` into `0`.
+        fn y(p: Vector) -> f64 {
+            -p.north + 0.0
+        }
+        fn polyline(points: impl Iterator<Item = Vector>, style: &str) -> String {
+            format!(
+                "  <polyline fill=\"none\" vector-effect=\"non-scaling-stroke\" {} points=\"{}\"/>\n",
+                style,
+                points.map(|p| format!("{},{}", p.east, y(p))).join(" ")
+            )
+        }
+        fn marker(p: Vector, radius: f64, colour: &str) -> String {
+            format!(
+                "  <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>\n",
+                p.east,
+                y(p),
+                radius,
+                colour
+            )
+        }
+
+        let (min, max) = match self.navigation {
+            Navigation::Heading => self.bounding_box(),
+            Navigation::Waypoint => bounding_box(self.positions().chain(self.waypoints())),
+        };
+        let margin = ((max.north - min.north).max(max.east - min.east) / 20.0).max(1.0);
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n",
+            min.east - margin,
+            -max.north - margin,
+            max.east - min.east + 2.0 * margin,
+            max.north - min.north + 2.0 * margin
+        );
+        if self.navigation == Navigation::Waypoint {
+            svg += &polyline(self.waypoints(), "stroke=\"grey\" stroke-dasharray=\"4\"");
+        }
+        svg += &polyline(self.positions(), "stroke=\"black\"");
+        svg += &marker(Vector::new(0.0, 0.0), margin / 2.0, "green");
+        svg += &marker(self.furthest(), margin / 2.0, "red");
+        svg += "</svg>\n";
+        svg
+    }
+}
+
+fn bounding_box(points: impl Iterator<Item = Vector>) -> (Vector, Vector) {
+    points.fold(
+        (
+            Vector::new(f64::INFINITY, f64::INFINITY),
+            Vector::new(f64::NEG_INFINITY, f64::NEG_INFINITY),
+        ),
+        |(min, max), p| {
+            (
+                Vector::new(min.north.min(p.north), min.east.min(p.east)),
+                Vector::new(max.north.max(p.north), max.east.max(p.east)),
+            )
+        },
+    )
 }
 
 #[aoc_generator(day12)]
-pub fn input_generator(input: &str) -> Vec<(char, i16)> {
-    input
-        .lines()
-        .map(|l| (l.chars().next().unwrap(), l[1..].parse().unwrap()))
-        .collect_vec()
+pub fn input_generator(input: &str) -> anyhow::Result<Vec<Action>> {
+    fn parse_value(input: &str) -> IResult<&str, i32> {
+        unsigned(input)
+    }
+    /// Turns by more than a full circle are most likely corrupted input.
+    fn parse_rotation(input: &str) -> IResult<&str, i32> {
+        context(
+            "rotation between 0 and 360 degrees",
+            verify(parse_value, |d| (0..=360).contains(d)),
+        )(input)
+    }
+    fn parse_action(input: &str) -> IResult<&str, Action> {
+        context(
+            "action",
+            alt((
+                map(preceded(char('N'), cut(parse_value)), Action::North),
+                map(preceded(char('S'), cut(parse_value)), Action::South),
+                map(preceded(char('E'), cut(parse_value)), Action::East),
+                map(preceded(char('W'), cut(parse_value)), Action::West),
+                map(preceded(char('L'), cut(parse_rotation)), Action::Left),
+                map(preceded(char('R'), cut(parse_rotation)), Action::Right),
+                map(preceded(char('F'), cut(parse_value)), Action::Forward),
+            )),
+        )(input)
+    }
+
+    Ok(parsing::lines(input, parse_action)?)
 }
 
 #[aoc(day12, part1)]
-pub fn part1(input: &[(char, i16)]) -> i16 {
-    let result = input.iter().fold(Ship::default(), |s, instr| {
-        // println!("{:?}\t{:?}", s, instr);
-        s.process(*instr)
+#[instrument(level = "info", skip_all)]
+pub fn part1(input: &[Action]) -> i64 {
+    let result = input.iter().fold(Ship::default(), |s, action| {
+        trace!(ship = ?s, ?action);
+        s.process(*action)
     });
+    info!(ship = ?result, "arrived");
     result.manhattan_distance()
 }
 
 #[aoc(day12, part2)]
-pub fn part2(input: &[(char, i16)]) -> i16 {
-    let result = input.iter().fold(Ship::default(), |s, instr| {
-        // println!("{:?}\t{:?}", s, instr);
-        s.process2(*instr)
+#[instrument(level = "info", skip_all)]
+pub fn part2(input: &[Action]) -> i64 {
+    let result = input.iter().fold(Ship::default(), |s, action| {
+        trace!(ship = ?s, ?action);
+        s.process2(*action)
     });
+    info!(ship = ?result, "arrived");
     result.manhattan_distance()
 }
 
@@ -171,35 +357,152 @@ mod tests {
     use std::fs::read_to_string;
 
     #[test]
-    fn part1_example() {
-        let input = "\
-F10
-N3
-F7
-R90
-F11";
-        assert_eq!(25, super::part1(&super::input_generator(&input)));
+    fn part1() {
+        let input = read_to_string("input/2020/day12.txt").expect("input file missing");
+        assert_eq!(362, super::part1(&super::input_generator(&input).unwrap()));
     }
+
     #[test]
-    fn part2_example() {
-        let input = "\
-F10
-N3
-F7
-R90
-F11";
-        assert_eq!(286, super::part2(&super::input_generator(&input)));
+    fn part2() {
+        let input = read_to_string("input/2020/day12.txt").expect("input file missing");
+        assert_eq!(
+            29895,
+            super::part2(&super::input_generator(&input).unwrap())
+        );
     }
 
     #[test]
-    fn part1() {
-        let input = read_to_string("input/2020/day12.txt").expect("input file missing");
-        assert_eq!(362, super::part1(&super::input_generator(&input)));
+    fn right_angle_headings() {
+        use super::Ship;
+        // (turn, north, east) after turning left by `turn` and moving forward 5
+        for &(turn, north, east) in &[
+            (0, 0.0, 5.0),
+            (90, 5.0, 0.0),
+            (180, 0.0, -5.0),
+            (270, -5.0, 0.0),
+            (360, 0.0, 5.0),
+            (-90, -5.0, 0.0),
+            (450, 5.0, 0.0),
+            (-720, 0.0, 5.0),
+        ] {
+            let ship = Ship::default().turn_ship(turn).forward_ship(5);
+            assert_eq!(north, ship.position.north, "turn {}", turn);
+            assert_eq!(east, ship.position.east, "turn {}", turn);
+        }
     }
 
     #[test]
-    fn part2() {
-        let input = read_to_string("input/2020/day12.txt").expect("input file missing");
-        assert_eq!(29895, super::part2(&super::input_generator(&input)));
+    fn every_heading() {
+        use super::{Action, Ship, Vector};
+        for degrees in -360..=360 {
+            let ship = Ship::default()
+                .process(Action::Left(degrees))
+                .process(Action::Forward(100));
+            let (sin, cos) = f64::from(degrees).to_radians().sin_cos();
+            assert!(
+                (ship.position.north - 100.0 * sin).abs() < 1e-9,
+                "north at {}: {:?}",
+                degrees,
+                ship.position
+            );
+            assert!(
+                (ship.position.east - 100.0 * cos).abs() < 1e-9,
+                "east at {}: {:?}",
+                degrees,
+                ship.position
+            );
+
+            let back = Ship::default()
+                .process2(Action::Left(degrees))
+                .process2(Action::Right(degrees));
+            assert!((back.waypoint.north - 1.0).abs() < 1e-9);
+            assert!((back.waypoint.east - 10.0).abs() < 1e-9);
+            let turned = Vector::new(1.0, 10.0).rotate(degrees);
+            assert!((turned.north.hypot(turned.east) - 101f64.sqrt()).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn long_routes_do_not_overflow() {
+        let input = vec![super::Action::Forward(30000); 10];
+        assert_eq!(300000, super::part1(&input));
+        assert_eq!(3300000, super::part2(&input));
+    }
+
+    fn example() -> String {
+        read_to_string("examples/2020/day12/example.txt").unwrap()
+    }
+
+    #[test]
+    fn track_example() {
+        use super::{Navigation, Track, Vector};
+        let input = super::input_generator(&example()).unwrap();
+
+        let track = Track::follow(&input, Navigation::Heading);
+        assert_eq!(6, track.positions().count());
+        assert_eq!(Vector::new(-8.0, 17.0), track.end());
+        assert_eq!(
+            (Vector::new(-8.0, 0.0), Vector::new(3.0, 17.0)),
+            track.bounding_box()
+        );
+        assert_eq!(10.0 + 3.0 + 7.0 + 11.0, track.length());
+        assert_eq!(Vector::new(-8.0, 17.0), track.furthest());
+
+        let track = Track::follow(&input, Navigation::Waypoint);
+        assert_eq!(Vector::new(-72.0, 214.0), track.furthest());
+    }
+
+    #[test]
+    fn track_svg() {
+        use super::{Navigation, Track};
+        let input = super::input_generator(&example()).unwrap();
+        let svg = Track::follow(&input, Navigation::Heading).to_svg();
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains("points=\"0,0 10,0 10,-3 17,-3 17,-3 17,8\""));
+        assert_eq!(1, svg.matches("<polyline").count());
+
+        let svg = Track::follow(&input, Navigation::Waypoint).to_svg();
+        assert_eq!(2, svg.matches("<polyline").count());
+    }
+
+    #[test]
+    fn parse_actions() {
+        use super::Action;
+        assert_eq!(
+            vec![
+                Action::North(3),
+                Action::South(4),
+                Action::East(5),
+                Action::West(6),
+                Action::Left(90),
+                Action::Right(270),
+                Action::Forward(7)
+            ],
+            super::input_generator("N3\nS4\nE5\nW6\nL90\nR270\nF7\n").unwrap()
+        );
+        assert_eq!(
+            vec![Action::Forward(1), Action::Left(45)],
+            super::input_generator("F1\r\nL45\r\n").unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        for (input, line) in &[
+            ("F10\nX3\nF7", "line 2"),
+            ("F10\nN3\nF", "line 3"),
+            ("F10\nR400", "line 2"),
+            ("F10\nL-90", "line 2"),
+            ("F10\nN3x", "line 2"),
+        ] {
+            let err = super::input_generator(input).unwrap_err().to_string();
+            assert!(err.contains(line), "{:?}: {}", input, err);
+        }
+        let err = super::input_generator("F10\nR400").unwrap_err().to_string();
+        assert!(
+            err.contains("rotation between 0 and 360 degrees"),
+            "{}",
+            err
+        );
     }
 }
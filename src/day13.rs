@@ -1,8 +1,20 @@
+use std::convert::TryFrom;
+
 use itertools::Itertools;
+use nom::{
+    branch::alt,
+    character::complete::{char, line_ending},
+    combinator::{map, value, verify},
+    error::context,
+    sequence::separated_pair,
+};
+use num::integer::gcd;
+
+use crate::parsing::{self, unsigned};
 
 pub struct Schedule {
     start: u32,
-    busses: Vec<Option<u32>>,
+    pub(crate) busses: Vec<Option<u32>>,
 }
 
 impl Schedule {
@@ -21,30 +33,60 @@ impl Schedule {
         (time, bus)
     }
 
-    fn get_timestamp_subsequent(&self) -> usize {
-        (0..)
-            .step_by(self.busses.first().unwrap().unwrap() as usize)
-            .find(|timestamp| {
-                self.busses
-                    .iter()
-                    .enumerate()
-                    .filter_map(|(i, x)| x.map(|b| (i, b)))
-                    .all(|(i, b)| (timestamp + i) as u32 % b == 0)
-            })
-            .unwrap()
+    /// Sieves one bus at a time: once a timestamp suits the buses so far, stepping by the product
+    /// of their IDs, which share no factor, keeps it suiting them while looking for the next bus.
+    fn get_timestamp_subsequent(&self) -> anyhow::Result<usize> {
+        // otherwise there may be no answer, and stepping by the product wouldn't find it
+        if let Some((a, b)) = self
+            .busses
+            .iter()
+            .flatten()
+            .tuple_combinations()
+            .find(|(&a, &b)| gcd(a, b) != 1)
+        {
+            anyhow::bail!("bus IDs {} and {} share a factor", a, b);
+        }
+        let mut timestamp = 0u64;
+        // `None` once the product is past `u64::MAX`, leaving only the timestamp so far in range
+        let mut step = Some(1u64);
+        for (i, b) in self
+            .busses
+            .iter()
+            .enumerate()
+            .filter_map(|(i, x)| x.map(|b| (i as u64, u64::from(b))))
+        {
+            timestamp = (0..b)
+                .map(|k| match k {
+                    0 => Some(timestamp),
+                    _ => step?.checked_mul(k)?.checked_add(timestamp),
+                })
+                .take_while(Option::is_some)
+                .flatten()
+                .find(|t| (t % b + i % b) % b == 0)
+                .ok_or_else(|| {
+                    anyhow::anyhow!("no timestamp up to u64::MAX suits bus {} at {}", b, i)
+                })?;
+            step = step.and_then(|step| step.checked_mul(b));
+        }
+        Ok(usize::try_from(timestamp)?)
     }
 }
 
 #[aoc_generator(day13)]
 pub fn input_generator(input: &str) -> anyhow::Result<Schedule> {
-    let lines = input.lines().collect_vec();
-    Ok(Schedule {
-        start: lines[0].parse()?,
-        busses: lines[1]
-            .split(',')
-            .map(|s| s.parse().ok())
-            .collect::<Vec<Option<u32>>>(),
-    })
+    let bus = alt((
+        value(None, char('x')),
+        map(
+            context("bus ID above 0", verify(unsigned, |&id| id > 0)),
+            Some,
+        ),
+    ));
+    let (start, busses) = parsing::parse(
+        input,
+        separated_pair(unsigned, line_ending, parsing::comma_list(bus)),
+    )?;
+    anyhow::ensure!(busses.iter().any(Option::is_some), "no bus in service");
+    Ok(Schedule { start, busses })
 }
 
 #[aoc(day13, part1)]
@@ -53,8 +95,8 @@ pub fn part1(input: &Schedule) -> u32 {
     time * bus
 }
 
-// #[aoc(day13, part2)]
-pub fn part2(input: &Schedule) -> usize {
+#[aoc(day13, part2)]
+pub fn part2(input: &Schedule) -> anyhow::Result<usize> {
     input.get_timestamp_subsequent()
 }
 
@@ -67,28 +109,33 @@ mod tests {
         let input = read_to_string("input/2020/day13.txt").expect("input file missing");
         assert_eq!(205, super::part1(&super::input_generator(&input).unwrap()));
     }
+
     #[test]
-    fn part1_example() {
-        let input = "\
-939
-7,13,x,x,59,x,31,19";
-        assert_eq!(295, super::part1(&super::input_generator(input).unwrap()));
-    }
-    #[test]
-    fn part2_example() {
-        let input = "\
-939
-7,13,x,x,59,x,31,19";
+    fn part2() {
+        let input = read_to_string("input/2020/day13.txt").expect("input file missing");
         assert_eq!(
-            1068781,
-            super::part2(&super::input_generator(input).unwrap())
+            803025030761664,
+            super::part2(&super::input_generator(&input).unwrap()).unwrap()
         );
     }
 
     #[test]
-    #[ignore]
-    fn part2() {
-        let input = read_to_string("input/2020/day13.txt").expect("input file missing");
-        assert_eq!(0, super::part2(&super::input_generator(&input).unwrap()));
+    fn invalid_schedules() {
+        assert!(super::input_generator("1\nx,x").is_err());
+
+        // part 1 doesn't mind IDs sharing a factor, part 2 can't sieve by them
+        let schedule = super::input_generator("939\n2,4").unwrap();
+        assert_eq!(2, super::part1(&schedule));
+        assert!(super::part2(&schedule).is_err());
+        let schedule = super::input_generator("1\n7,x,7").unwrap();
+        assert_eq!(42, super::part1(&schedule));
+        assert!(super::part2(&schedule).is_err());
+        let schedule = super::input_generator("1\n1,7").unwrap();
+        assert_eq!(6, super::part2(&schedule).unwrap());
+
+        // the product of these primes is past u64::MAX, and so is the timestamp
+        let primes = "47,53,59,61,67,71,73,79,83,89,97";
+        let schedule = super::input_generator(&format!("1\n{}", primes)).unwrap();
+        assert!(super::part2(&schedule).is_err());
     }
 }
@@ -1,14 +1,15 @@
 use bit_vec::BitVec;
-use itertools::Itertools;
 use nom::{
     branch::alt,
-    bytes::complete::tag,
-    character::complete::{digit1, not_line_ending},
-    combinator::{all_consuming, map, map_res},
+    bytes::complete::{tag, take_while_m_n},
+    combinator::{map, verify},
+    error::context,
     sequence::{preceded, tuple},
-    IResult,
 };
 use std::collections::HashMap;
+use tracing::{debug, info, instrument, trace};
+
+use crate::parsing::{self, unsigned, IResult};
 
 pub enum Instr {
     Mask(String),
@@ -61,20 +62,25 @@ fn apply_mask(curr: &mut BitVec, mask_set_0: &BitVec, mask_set_1: &BitVec) {
 }
 
 #[aoc_generator(day14)]
-pub fn input_generator(input: &str) -> Vec<Instr> {
+pub fn input_generator(input: &str) -> anyhow::Result<Vec<Instr>> {
     fn mask(input: &str) -> IResult<&str, Instr> {
-        map(preceded(tag("mask = "), not_line_ending), |s: &str| {
-            Instr::Mask(s.to_string())
-        })(input)
-    }
-
-    fn parse_prim<T: std::str::FromStr>(input: &str) -> IResult<&str, T> {
-        map_res(digit1, |i: &str| i.parse::<T>())(input)
+        map(
+            preceded(
+                tag("mask = "),
+                context("36 bit mask", take_while_m_n(36, 36, |c| "01X".contains(c))),
+            ),
+            |s: &str| Instr::Mask(s.to_string()),
+        )(input)
     }
 
     fn mem(input: &str) -> IResult<&str, Instr> {
         map(
-            tuple((tag("mem["), parse_prim, tag("] = "), parse_prim::<u64>)),
+            tuple((
+                tag("mem["),
+                unsigned,
+                tag("] = "),
+                context("36 bit value", verify(unsigned::<u64>, |v| *v < 1 << 36)),
+            )),
             |(_, addr, _, val)| {
                 Instr::Mem(
                     addr,
@@ -91,52 +97,171 @@ pub fn input_generator(input: &str) -> Vec<Instr> {
         )(input)
     }
 
-    fn parse(input: &str) -> IResult<&str, Instr> {
-        all_consuming(alt((mask, mem)))(input)
-    }
-
-    input.lines().map(|l| parse(l).unwrap().1).collect_vec()
+    Ok(parsing::lines(input, alt((mask, mem)))?)
 }
 
 #[aoc(day14, part1)]
+#[instrument(level = "info", skip_all)]
 pub fn part1(input: &[Instr]) -> u64 {
     let mut mem = Memory::default();
     for instr in input {
         mem.process(instr);
     }
+    info!(addresses = mem.mem.len(), "written");
     mem.count_values()
 }
 
+pub(crate) fn to_u64(bits: &BitVec) -> u64 {
+    bits.iter().fold(0, |acc, bit| acc << 1 | u64::from(bit))
+}
+
+/// A part 2 mask as the address bits it sets and the ones it leaves floating.
+pub(crate) fn floating_mask(mask: &str) -> (u64, u64) {
+    mask.chars().fold((0, 0), |(ones, floating), c| {
+        (
+            ones << 1 | u64::from(c == '1'),
+            floating << 1 | u64::from(c == 'X'),
+        )
+    })
+}
+
+/// The addresses matching `fixed` on every bit outside `floating`.
+#[derive(Clone, Copy, Debug)]
+struct Addresses {
+    fixed: u64,
+    floating: u64,
+}
+
+impl Addresses {
+    fn len(self) -> u64 {
+        1 << self.floating.count_ones()
+    }
+
+    /// `self` without the addresses in `other`, as disjoint sets.
+    fn minus(self, other: Addresses, out: &mut Vec<Addresses>) {
+        let both_fixed = !self.floating & !other.floating;
+        if (self.fixed ^ other.fixed) & both_fixed != 0 {
+            out.push(self);
+            return;
+        }
+        // pin down one bit `other` fixes at a time: the opposite value leaves `other`, the same
+        // value carries on to the next bit
+        let mut rest = self;
+        let mut bits = self.floating & !other.floating;
+        while bits != 0 {
+            let bit = bits & bits.wrapping_neg();
+            bits &= !bit;
+            rest.floating &= !bit;
+            out.push(Addresses {
+                fixed: rest.fixed & !bit | !other.fixed & bit,
+                floating: rest.floating,
+            });
+            rest.fixed = rest.fixed & !bit | other.fixed & bit;
+        }
+        // whatever is left lies inside `other`
+    }
+}
+
+/// Keeps the memory as disjoint sets of addresses sharing a value, so floating bits never have
+/// to be expanded.
 #[aoc(day14, part2)]
+#[instrument(level = "info", skip_all)]
 pub fn part2(input: &[Instr]) -> anyhow::Result<usize> {
-    Ok(0)
+    let mut mask = None;
+    let mut memory: Vec<(Addresses, u64)> = Vec::new();
+    for instr in input {
+        match instr {
+            Instr::Mask(m) => {
+                debug!(mask = %m);
+                mask = Some(floating_mask(m));
+            }
+            Instr::Mem(address, value) => {
+                let (ones, floating) =
+                    mask.ok_or_else(|| anyhow::anyhow!("write before a mask"))?;
+                let written = Addresses {
+                    fixed: (u64::from(*address) | ones) & !floating,
+                    floating,
+                };
+                let mut remaining = Vec::with_capacity(memory.len() + 1);
+                let mut pieces = Vec::new();
+                for (addresses, v) in memory {
+                    addresses.minus(written, &mut pieces);
+                    remaining.extend(pieces.drain(..).map(|a| (a, v)));
+                }
+                remaining.push((written, to_u64(value)));
+                memory = remaining;
+                trace!(address, sets = memory.len(), "written");
+            }
+        }
+    }
+    info!(sets = memory.len(), "written");
+    Ok(memory.iter().map(|(a, v)| a.len() * v).sum::<u64>() as usize)
 }
 
 #[cfg(test)]
 mod tests {
-    use std::fs::read_to_string;
+    use std::{collections::HashSet, fs::read_to_string};
 
-    #[test]
-    fn part1() {
-        let input = "\
-mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
-mem[8] = 11
-mem[7] = 101
-mem[8] = 0";
-        assert_eq!(165, super::part1(&super::input_generator(&input)));
+    use super::Addresses;
+    use crate::reference;
+
+    fn expand(addresses: Addresses) -> HashSet<u64> {
+        (0..16)
+            .filter(|a| (a ^ addresses.fixed) & !addresses.floating == 0)
+            .collect()
     }
+
     #[test]
-    fn part1_example() {
+    fn part1() {
         let input = read_to_string("input/2020/day14.txt").expect("input file missing");
         assert_eq!(
             14553106347726,
-            super::part1(&super::input_generator(&input))
+            super::part1(&super::input_generator(&input).unwrap())
         );
     }
 
     #[test]
     fn part2() {
         let input = read_to_string("input/2020/day14.txt").expect("input file missing");
-        assert_eq!(0, super::part2(&super::input_generator(&input)).unwrap());
+        assert_eq!(
+            2737766154126,
+            super::part2(&super::input_generator(&input).unwrap()).unwrap()
+        );
+    }
+
+    /// Every pair of address sets over 4 bits, against the sets spelled out.
+    #[test]
+    fn minus() {
+        let sets = (0..16u64)
+            .flat_map(|floating| {
+                (0..16u64)
+                    .filter(move |fixed| fixed & floating == 0)
+                    .map(move |fixed| Addresses { fixed, floating })
+            })
+            .collect::<Vec<_>>();
+        for &a in &sets {
+            for &b in &sets {
+                let mut pieces = Vec::new();
+                a.minus(b, &mut pieces);
+                let expected = &expand(a) - &expand(b);
+                let mut covered = HashSet::new();
+                for piece in pieces {
+                    assert!(expand(piece).is_disjoint(&covered), "{:?} - {:?}", a, b);
+                    assert_eq!(expand(piece).len() as u64, piece.len());
+                    covered.extend(expand(piece));
+                }
+                assert_eq!(expected, covered, "{:?} - {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn part2_matches_expanding() {
+        let input = read_to_string("input/2020/day14.txt").expect("input file missing");
+        let program = super::input_generator(&input).unwrap();
+        assert_eq!(
+            reference::day14::floating_memory_sum(&program) as usize,
+            super::part2(&program).unwrap()
+        );
     }
 }
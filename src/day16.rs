@@ -2,16 +2,16 @@ use anyhow::Result;
 use anyhow::*;
 use itertools::Itertools;
 use nom::{
-    branch::alt,
     bytes::complete::{is_not, tag},
-    character::complete::{digit1, newline},
-    combinator::{all_consuming, eof, map, map_res},
-    error::VerboseError,
+    character::complete::newline,
+    combinator::map,
     multi::separated_list1,
     sequence::{preceded, separated_pair, tuple},
-    Finish,
 };
 use std::ops::RangeInclusive;
+use tracing::{debug, instrument, trace};
+
+use crate::parsing::{self, unsigned, IResult};
 
 #[derive(Debug)]
 struct Field {
@@ -85,17 +85,10 @@ impl Scan {
     }
 }
 
-type IResult<I, O> = nom::IResult<I, O, nom::error::VerboseError<I>>;
-
 #[aoc_generator(day16)]
 pub fn input_generator(input: &str) -> anyhow::Result<Scan> {
-    fn parse_u32(input: &str) -> IResult<&str, u32> {
-        map_res(digit1, |r: &str| r.parse::<u32>())(input)
-    }
     fn parse_range(input: &str) -> IResult<&str, RangeInclusive<u32>> {
-        map(separated_pair(parse_u32, tag("-"), parse_u32), |(a, b)| {
-            a..=b
-        })(input)
+        map(separated_pair(unsigned, tag("-"), unsigned), |(a, b)| a..=b)(input)
     }
     fn parse_rules(input: &str) -> IResult<&str, Vec<RangeInclusive<u32>>> {
         map(
@@ -116,7 +109,7 @@ pub fn input_generator(input: &str) -> anyhow::Result<Scan> {
         separated_list1(newline, parse_field)(input)
     }
     fn parse_ticket(input: &str) -> IResult<&str, Ticket> {
-        map(separated_list1(tag(","), parse_u32), Ticket)(input)
+        map(parsing::comma_list(unsigned), Ticket)(input)
     }
     fn parse_my_tickets(input: &str) -> IResult<&str, Ticket> {
         preceded(tuple((tag("your ticket:"), newline)), parse_ticket)(input) //add newlines
@@ -128,49 +121,44 @@ pub fn input_generator(input: &str) -> anyhow::Result<Scan> {
         )(input)
     }
 
-    map(
-        all_consuming(tuple((
-            parse_field_list,
-            newline,
-            newline,
-            parse_my_tickets,
-            newline,
-            newline,
-            parse_tickets,
-            alt((preceded(newline, eof), eof)),
-        ))),
-        |(f, _, _, m, _, _, t, _)| Scan {
-            fields: f,
-            my_ticket: m,
-            tickets: t,
-        },
-    )(input)
-    .finish()
-    .map(|(rest, parsed)| {
-        assert_eq!("", rest);
-        parsed
-    })
-    .map_err(|e: VerboseError<&str>| {
-        anyhow!("Parsing failed: {}", nom::error::convert_error(input, e))
-    })
+    Ok(parsing::parse(
+        input,
+        map(
+            tuple((
+                parse_field_list,
+                newline,
+                newline,
+                parse_my_tickets,
+                newline,
+                newline,
+                parse_tickets,
+            )),
+            |(f, _, _, m, _, _, t)| Scan {
+                fields: f,
+                my_ticket: m,
+                tickets: t,
+            },
+        ),
+    )?)
 }
 
 // #[aoc(day16, part1)]
+#[instrument(level = "info", skip_all)]
 pub fn part1(input: &Scan) -> u32 {
     let rules = input
         .fields
         .iter()
         .flat_map(|f| f.rules.iter())
         .collect_vec();
-    // dbg!(&rules);
-    // dbg!(&input.tickets);
+    debug!(?rules);
+    trace!(tickets = ?input.tickets);
     let invalid = input
         .tickets
         .iter()
         .flat_map(|t| t.0.iter())
         .filter(|n| !rules.iter().any(|range| range.contains(n)))
         .collect_vec();
-    // dbg!(&invalid);
+    debug!(?invalid);
     invalid.iter().copied().sum()
 }
 
@@ -188,24 +176,6 @@ pub fn part2(input: &Scan) -> anyhow::Result<u32> {
 mod tests {
     use std::fs::read_to_string;
 
-    #[test]
-    fn parse_example() {
-        let input = "\
-class: 1-3 or 5-7
-row: 6-11 or 33-44
-seat: 13-40 or 45-50
-
-your ticket:
-7,1,14
-
-nearby tickets:
-7,3,47
-40,4,50
-55,2,20
-38,6,12";
-        assert_eq!(71, super::part1(&super::input_generator(&input).unwrap()));
-    }
-
     #[test]
     fn part1() -> anyhow::Result<()> {
         let input = read_to_string("input/2020/day16.txt")?;
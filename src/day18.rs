@@ -1,9 +1,8 @@
 use core::panic;
 
-use nom::{
-    branch::*, character::complete::*, combinator::*, error::convert_error, multi::*, sequence::*,
-    Finish,
-};
+use nom::{branch::*, character::complete::*, combinator::*, multi::*, sequence::*};
+
+use crate::parsing::{self, unsigned, IResult};
 
 enum Op {
     Add,
@@ -40,14 +39,11 @@ impl Computable for SubExp {
     }
 }
 
-type IResult<I, O> = nom::IResult<I, O, nom::error::VerboseError<I>>;
+/// Far deeper than any homework, while both parsing and evaluating recurse once per level.
+const MAX_NESTING: usize = 64;
 
 #[aoc_generator(day18)]
 pub fn input_generator(input: &str) -> anyhow::Result<Vec<Expression>> {
-    /// `0`
-    fn parse_u64(input: &str) -> IResult<&str, u64> {
-        map_res(digit1, |r: &str| r.parse::<u64>())(input)
-    }
     /// `+`
     fn parse_op(input: &str) -> IResult<&str, Op> {
         map(terminated(one_of("+*"), char(' ')), |r| match r {
@@ -60,7 +56,7 @@ pub fn input_generator(input: &str) -> anyhow::Result<Vec<Expression>> {
     /// `(0 + 1)`
     fn parse_subexp(input: &str) -> IResult<&str, SubExp> {
         alt((
-            map(parse_u64, SubExp::Number),
+            map(unsigned, SubExp::Number),
             map(
                 preceded(char('('), terminated(parse_expression, char(')'))),
                 SubExp::Parentheses,
@@ -81,16 +77,28 @@ pub fn input_generator(input: &str) -> anyhow::Result<Vec<Expression>> {
             },
         )(input)
     }
-    fn parse_line(input: &str) -> anyhow::Result<Expression> {
-        all_consuming(parse_expression)(input)
-            .finish()
-            .map(|(_rest, res)| res)
-            .map_err(|e| anyhow::anyhow!("Parser error:\n{}\n", convert_error(input, e)))
+    fn nesting(line: &str) -> i32 {
+        line.chars()
+            .scan(0i32, |depth, c| {
+                match c {
+                    '(' => *depth += 1,
+                    ')' => *depth -= 1,
+                    _ => {}
+                }
+                Some(*depth)
+            })
+            .max()
+            .unwrap_or(0)
+    }
+    for (i, line) in input.lines().enumerate() {
+        anyhow::ensure!(
+            nesting(line) <= MAX_NESTING as i32,
+            "line {}: parentheses nested deeper than {}",
+            i + 1,
+            MAX_NESTING
+        );
     }
-    input
-        .lines()
-        .map(parse_line)
-        .collect::<anyhow::Result<Vec<Expression>>>()
+    Ok(parsing::lines(input, parse_expression)?)
 }
 
 #[aoc(day18, part1)]
@@ -107,39 +115,14 @@ pub fn par21(input: &[Expression]) -> anyhow::Result<usize> {
 mod tests {
     use std::fs::read_to_string;
 
-    use anyhow::Result;
-
     use super::{input_generator, Computable};
 
     #[test]
-    fn parse_example() -> Result<()> {
-        input_generator("(2 * 3) + 1")?;
-        input_generator("2 * 3 + 20")?;
-        input_generator("2 * 3 + (4 * 5)")?;
-        input_generator("5 + (8 * 3 + 9 + 3 * 4 * 3)")?;
-        input_generator("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))")?;
-        input_generator("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2")?;
-        Ok(())
-    }
-    #[test]
-    fn part1_example() -> Result<()> {
-        assert_eq!(6, input_generator("2 * 3")?[0].get_result());
-        assert_eq!(26, input_generator("2 * 3 + (4 * 5)")?[0].get_result());
-        assert_eq!(
-            437,
-            input_generator("5 + (8 * 3 + 9 + 3 * 4 * 3)")?[0].get_result()
-        );
-        assert_eq!(
-            12240,
-            input_generator("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))")?[0].get_result()
-        );
-        assert_eq!(
-            13632,
-            input_generator("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2")?[0].get_result()
-        );
-        Ok(())
+    fn deep_nesting() {
+        let nested = |depth| format!("1 + {}1 + 1{}", "(1 + ".repeat(depth), ")".repeat(depth));
+        assert_eq!(67, input_generator(&nested(64)).unwrap()[0].get_result());
+        assert!(input_generator(&nested(100_000)).is_err());
     }
-
     #[test]
     fn part1() {
         let input = read_to_string("input/2020/day18.txt").expect("input file missing");
@@ -4,62 +4,45 @@ use bit_vec::BitVec;
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    character::complete::digit1,
-    combinator::{map, map_res},
-    multi::{count, separated_list1},
-    sequence::{separated_pair, tuple},
-    Finish, IResult,
-};
-use nom::{
     character::complete::{char, newline},
-    sequence::terminated,
+    combinator::{map, value, verify},
+    error::context,
+    sequence::{delimited, separated_pair},
 };
+use tracing::debug;
+
+use crate::parsing::{self, blocks, grid, unsigned, IResult};
 
 #[aoc_generator(day20)]
 pub fn input_generator(input: &str) -> anyhow::Result<HashMap<u16, BitVec>> {
-    fn parse_num<T: std::str::FromStr>(input: &str) -> IResult<&str, T> {
-        map_res(digit1, |r: &str| r.parse::<T>())(input)
-    }
     fn parse_heading(input: &str) -> IResult<&str, u16> {
-        map(
-            tuple((tag("Tile "), parse_num::<u16>, tag(":"))),
-            |(_, id, _)| id,
-        )(input)
+        delimited(tag("Tile "), unsigned, tag(":"))(input)
     }
     fn parse_char(input: &str) -> IResult<&str, bool> {
-        alt((map(char('.'), |c| false), map(char('#'), |c| true)))(input)
-    }
-    fn parse_line(input: &str) -> IResult<&str, BitVec> {
-        map(count(parse_char, 10), |r| r.into_iter().collect::<BitVec>())(input)
+        alt((value(false, char('.')), value(true, char('#'))))(input)
     }
-    fn parse_lines(input: &str) -> IResult<&str, BitVec> {
-        map(count(terminated(parse_line, newline), 10), |lines| {
-            lines.into_iter().fold(BitVec::new(), |mut acc, x| {
-                acc.extend(x);
-                acc
-            })
-        })(input)
-    }
-    fn parse_piece(input: &str) -> IResult<&str, (u16, BitVec)> {
+    fn parse_image(input: &str) -> IResult<&str, BitVec> {
         map(
-            separated_pair(parse_heading, newline, parse_lines),
-            |(heading, lines)| (heading, lines),
+            context(
+                "10 by 10 image",
+                verify(grid(parse_char), |rows: &Vec<Vec<bool>>| {
+                    rows.len() == 10 && rows[0].len() == 10
+                }),
+            ),
+            |rows| rows.into_iter().flatten().collect(),
         )(input)
     }
-    fn parse_pieces(input: &str) -> IResult<&str, HashMap<u16, BitVec>> {
-        map(separated_list1(newline, parse_piece), |r| {
-            r.into_iter().collect()
-        })(input)
+    fn parse_piece(input: &str) -> IResult<&str, (u16, BitVec)> {
+        separated_pair(parse_heading, newline, parse_image)(input)
     }
-    parse_pieces(input)
-        .finish()
-        .map(|(_rest, result)| result)
-        .map_err(|e| anyhow::anyhow!("Parsing failed: {}", e))
+    Ok(parsing::parse(input, blocks(parse_piece))?
+        .into_iter()
+        .collect())
 }
 
 // #[aoc(day20, part1)]
 pub fn part1(input: &HashMap<u16, BitVec>) -> anyhow::Result<usize> {
-    dbg!(input);
+    debug!(tiles = input.len());
     todo!()
 }
 
@@ -72,6 +55,14 @@ pub fn part2(input: &HashMap<u16, BitVec>) -> anyhow::Result<usize> {
 mod tests {
     use std::fs::read_to_string;
 
+    #[test]
+    fn generator() {
+        let input = read_to_string("input/2020/day20.txt").expect("input file missing");
+        assert_eq!(144, super::input_generator(&input).unwrap().len());
+        let err = super::input_generator("Tile 1:\n#.\n.#\n").unwrap_err();
+        assert!(err.to_string().contains("10 by 10 image"), "{}", err);
+    }
+
     #[test]
     #[ignore]
     fn part1() {
@@ -1,48 +1,103 @@
-use std::num::ParseIntError;
+use std::collections::HashMap;
 
-use anyhow::Result;
+use anyhow::{bail, ensure, Result};
+
+use crate::parsing;
+
+const MODULUS: u64 = 20201227;
+const SUBJECT_NUMBER: u64 = 7;
 
 #[aoc_generator(day25)]
 pub fn input_generator(input: &str) -> anyhow::Result<(u64, u64)> {
-    let nums = input
-        .lines()
-        .map(|l| l.parse())
-        .collect::<std::result::Result<Vec<u64>, ParseIntError>>()?;
-    assert_eq!(2, nums.len());
+    let nums = parsing::lines(input, parsing::unsigned::<u64>)?;
+    ensure!(
+        2 == nums.len(),
+        "expected 2 public keys, got {}",
+        nums.len()
+    );
     Ok((nums[0], nums[1]))
 }
 
-fn transform_until(subject_number: u64, expected: u64) -> u64 {
-    let mut val = 1;
-    let mut loop_size = 0;
-    loop {
-        val *= subject_number;
-        val %= 20201227;
-        loop_size += 1;
-        if val == expected {
-            return loop_size;
+fn mul_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    (u128::from(a) * u128::from(b) % u128::from(modulus)) as u64
+}
+
+/// `base^exp mod modulus` by repeated squaring.
+fn transform(base: u64, mut exp: u64, modulus: u64) -> u64 {
+    let mut base = base % modulus;
+    let mut val = 1 % modulus;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            val = mul_mod(val, base, modulus);
         }
+        base = mul_mod(base, base, modulus);
+        exp >>= 1;
     }
+    val
 }
 
-fn transform(subject_number: u64, loops: u64) -> u64 {
+/// The public key for a secret loop size.
+pub(crate) fn public_key(loop_size: u64) -> u64 {
+    transform(SUBJECT_NUMBER, loop_size, MODULUS)
+}
+
+/// Smallest loop size `x` with `subject_number^x ≡ expected (mod modulus)`, found by
+/// baby-step giant-step in O(√modulus) time and memory.
+///
+/// `modulus` has to be prime, the inverse of the giant step is taken via Fermat's little theorem.
+fn transform_until(subject_number: u64, expected: u64, modulus: u64) -> Result<u64> {
+    ensure!(modulus > 1, "modulus {} is not a prime", modulus);
+    let subject_number = subject_number % modulus;
+    let expected = expected % modulus;
+    ensure!(
+        subject_number != 0,
+        "subject number is a multiple of the modulus {}",
+        modulus
+    );
+
+    // the order of the subject number divides modulus - 1, so every loop size is below that
+    let steps = ((modulus - 1) as f64).sqrt().ceil().max(1.0) as u64;
+    let mut baby_steps = HashMap::with_capacity(steps as usize);
     let mut val = 1;
-    for _ in 0..loops {
-        val *= subject_number;
-        val %= 20201227;
+    for j in 0..steps {
+        baby_steps.entry(val).or_insert(j);
+        val = mul_mod(val, subject_number, modulus);
     }
-    val
+
+    let giant_step = transform(
+        transform(subject_number, modulus - 2, modulus),
+        steps,
+        modulus,
+    );
+    let mut val = expected;
+    for i in 0..steps {
+        if let Some(j) = baby_steps.get(&val) {
+            return Ok(i * steps + j);
+        }
+        val = mul_mod(val, giant_step, modulus);
+    }
+    bail!(
+        "{} is not a power of {} modulo {}",
+        expected,
+        subject_number,
+        modulus
+    )
 }
 
 #[aoc(day25, part1)]
-pub fn part1(&(a, b): &(u64, u64)) -> u64 {
-    let a_loop_size = transform_until(7, a);
-    let b_loop_size = transform_until(7, b);
-
-    let a_enc = transform(a, b_loop_size);
-    let b_enc = transform(b, a_loop_size);
-    assert_eq!(a_enc, b_enc);
-    a_enc
+pub fn part1(&(a, b): &(u64, u64)) -> Result<u64> {
+    let a_loop_size = transform_until(SUBJECT_NUMBER, a, MODULUS)?;
+    let b_loop_size = transform_until(SUBJECT_NUMBER, b, MODULUS)?;
+
+    let a_enc = transform(a, b_loop_size, MODULUS);
+    let b_enc = transform(b, a_loop_size, MODULUS);
+    ensure!(
+        a_enc == b_enc,
+        "encryption keys differ: {} and {}",
+        a_enc,
+        b_enc
+    );
+    Ok(a_enc)
 }
 
 // #[aoc(day25, part2)]
@@ -59,10 +114,51 @@ mod tests {
         let input = read_to_string("input/2020/day25.txt").expect("input file missing");
         assert_eq!(
             7032853,
-            super::part1(&super::input_generator(&input).unwrap())
+            super::part1(&super::input_generator(&input).unwrap()).unwrap()
         );
     }
 
+    #[test]
+    fn transform() {
+        use super::transform;
+        assert_eq!(5764801, transform(7, 8, 20201227));
+        assert_eq!(17807724, transform(7, 11, 20201227));
+        assert_eq!(1, transform(7, 0, 20201227));
+        assert_eq!(0, transform(7, 0, 1));
+        assert_eq!(
+            (0..1000).fold(1, |v, _| v * 12345 % 1_000_000_007),
+            transform(12345, 1000, 1_000_000_007)
+        );
+    }
+
+    #[test]
+    fn transform_until() {
+        use super::{transform, transform_until};
+        assert_eq!(8, transform_until(7, 5764801, 20201227).unwrap());
+        assert_eq!(11, transform_until(7, 17807724, 20201227).unwrap());
+        assert_eq!(0, transform_until(7, 1, 20201227).unwrap());
+        for &(subject, exp, modulus) in &[
+            (5, 123_456_789, 1_000_000_007),
+            (2, 3, 11),
+            (3, 1_000_000, 998_244_353),
+        ] {
+            let key = transform(subject, exp, modulus);
+            let loop_size = transform_until(subject, key, modulus).unwrap();
+            assert_eq!(key, transform(subject, loop_size, modulus));
+            assert!(loop_size <= exp);
+        }
+    }
+
+    #[test]
+    fn transform_until_errors() {
+        use super::transform_until;
+        // 2 only generates {1, 2, 4} modulo 7
+        assert!(transform_until(2, 3, 7).is_err());
+        assert!(transform_until(14, 3, 7).is_err());
+        assert!(transform_until(7, 5, 1).is_err());
+        assert!(super::input_generator("5764801").is_err());
+    }
+
     #[test]
     #[ignore]
     fn part2() {
@@ -0,0 +1,239 @@
+//! Runs every puzzle example under `examples/2020`.
+//!
+//! An example is `examples/2020/day<N>/<name>.txt`, with its expected answers in `<name>.answers`
+//! next to it, one `part<K>: <answer>` per line. Parts the example doesn't cover are left out.
+//!
+//! The days before 10 live in their own crates under `legacy/`, and are solved through them; day
+//! 5 goes through `crate::day05`, which the runner uses too.
+//! Every example needs a solver for the parts it lists, and every solver an example.
+
+use std::{
+    fs::{read_dir, read_to_string},
+    path::Path,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::{day05, day10, day11, day12, day13, day14, day15, day16, day18, day25};
+
+type Solver = fn(&str) -> Result<String>;
+
+/// Solvers allowed to go without an example: day 15 part 2 plays 30 million rounds, too slow
+/// for an unoptimised test build.
+const TOO_SLOW: [(u32, u32); 1] = [(15, 2)];
+
+/// Parses numbers, one per line.
+fn numbers(input: &str) -> Result<Vec<u64>> {
+    Ok(input.lines().map(str::parse).collect::<Result<_, _>>()?)
+}
+
+/// The first number that isn't a sum of two of the 5 before it, as in the day 9 example; the
+/// real input looks back 25.
+fn first_invalid(numbers: &[u64]) -> Result<u64> {
+    let (_, n) = day09::invalid(numbers.iter().copied(), 5)
+        .next()
+        .ok_or_else(|| anyhow!("every number is valid"))?;
+    Ok(n)
+}
+
+/// The product of the only `k` expense entries summing to 2020.
+fn expenses(input: &str, k: usize) -> Result<String> {
+    let entries = input
+        .lines()
+        .map(str::parse)
+        .collect::<Result<Vec<i64>, _>>()?;
+    match day01::k_sum(&entries, k, 2020)?.as_slice() {
+        [tuple] => Ok(tuple.iter().product::<i64>().to_string()),
+        tuples => bail!("{} sets of entries sum to 2020", tuples.len()),
+    }
+}
+
+/// How many password entries follow the policy.
+fn passwords(input: &str, policy: &dyn day02::Policy) -> Result<String> {
+    let entries =
+        day02::parse_entries(input).map_err(|(line, e)| anyhow!("line {}: {}", line, e))?;
+    Ok(day02::report(&entries, &[policy]).valid[0].1.to_string())
+}
+
+fn passports(input: &str, schema: &day04::Schema) -> Result<String> {
+    let valid = records::records(input)
+        .filter(|passport| schema.is_valid(&passport.join("\n")))
+        .count();
+    Ok(valid.to_string())
+}
+
+fn shiny_gold() -> day07::Bag {
+    day07::Bag::new("shiny", "gold")
+}
+
+/// Runs the day's generator, then the part.
+fn solver(day: u32, part: u32) -> Option<Solver> {
+    Some(match (day, part) {
+        (1, 1) => |input| expenses(input, 2),
+        (1, 2) => |input| expenses(input, 3),
+        (2, 1) => |input| passwords(input, &day02::CountInRange),
+        (2, 2) => |input| passwords(input, &day02::XorPosition),
+        (3, 1) => |input| {
+            Ok(input
+                .parse::<day03::Map>()?
+                .trees_on_slope(3, 1)
+                .to_string())
+        },
+        (3, 2) => |input| {
+            let trees = input.parse::<day03::Map>()?.trees_on_slopes(&[
+                (1, 1),
+                (3, 1),
+                (5, 1),
+                (7, 1),
+                (1, 2),
+            ]);
+            Ok(trees.iter().product::<usize>().to_string())
+        },
+        // part 1 only asks for the required fields
+        (4, 1) => |input| {
+            let mut schema = day04::Schema::puzzle();
+            for field in &mut schema.fields {
+                field.validator = day04::Validator::Any;
+            }
+            passports(input, &schema)
+        },
+        (4, 2) => |input| passports(input, &day04::Schema::puzzle()),
+        (5, 1) => |input| Ok(day05::part1(&day05::input_generator(input)?)?.to_string()),
+        (5, 2) => |input| Ok(day05::part2(&day05::input_generator(input)?)?.to_string()),
+        (6, 1) => |input| {
+            let groups = day06::parse_groups(input)?;
+            Ok(groups
+                .iter()
+                .map(|g| g.anyone().count())
+                .sum::<usize>()
+                .to_string())
+        },
+        (6, 2) => |input| {
+            let groups = day06::parse_groups(input)?;
+            Ok(groups
+                .iter()
+                .map(|g| g.everyone().count())
+                .sum::<usize>()
+                .to_string())
+        },
+        (7, 1) => |input| {
+            let graph = day07::BagGraph::parse(input)?;
+            Ok(graph.containers_of(&shiny_gold())?.len().to_string())
+        },
+        (7, 2) => |input| {
+            let graph = day07::BagGraph::parse(input)?;
+            Ok(graph.count_inside(&shiny_gold())?.to_string())
+        },
+        (8, 1) => |input| {
+            let program = day08::parse_program(input)
+                .map_err(|(line, reason)| anyhow!("line {}: {}", line, reason))?;
+            Ok(day08::Machine::new(program).run().acc().to_string())
+        },
+        (8, 2) => |input| {
+            let program = day08::parse_program(input)
+                .map_err(|(line, reason)| anyhow!("line {}: {}", line, reason))?;
+            let patch = day08::repair(&program).map_err(|e| anyhow!("{:?}", e))?;
+            Ok(patch.acc.to_string())
+        },
+        (9, 1) => |input| Ok(first_invalid(&numbers(input)?)?.to_string()),
+        (9, 2) => |input| {
+            let numbers = numbers(input)?;
+            let run = day09::contiguous_sum(&numbers, first_invalid(&numbers)?)
+                .ok_or_else(|| anyhow!("no run adds up to the first invalid number"))?;
+            Ok((run.min + run.max).to_string())
+        },
+        (10, 1) => |input| Ok(day10::solve_part1(&day10::input_generator(input)?).to_string()),
+        (10, 2) => |input| Ok(day10::part2(&day10::input_generator(input)?).to_string()),
+        (11, 1) => |input| Ok(day11::part1(&day11::input_generator(input)?).to_string()),
+        (11, 2) => |input| Ok(day11::part2(&day11::input_generator(input)?)?.to_string()),
+        (12, 1) => |input| Ok(day12::part1(&day12::input_generator(input)?).to_string()),
+        (12, 2) => |input| Ok(day12::part2(&day12::input_generator(input)?).to_string()),
+        (13, 1) => |input| Ok(day13::part1(&day13::input_generator(input)?).to_string()),
+        (13, 2) => |input| Ok(day13::part2(&day13::input_generator(input)?)?.to_string()),
+        (14, 1) => |input| Ok(day14::part1(&day14::input_generator(input)?).to_string()),
+        (14, 2) => |input| Ok(day14::part2(&day14::input_generator(input)?)?.to_string()),
+        (15, 1) => |input| Ok(day15::part1(&day15::input_generator(input)?).to_string()),
+        (15, 2) => |input| Ok(day15::part2(&day15::input_generator(input)?).to_string()),
+        (16, 1) => |input| Ok(day16::part1(&day16::input_generator(input)?).to_string()),
+        (18, 1) => |input| Ok(day18::part1(&day18::input_generator(input)?).to_string()),
+        (25, 1) => |input| Ok(day25::part1(&day25::input_generator(input)?)?.to_string()),
+        _ => return None,
+    })
+}
+
+/// Parses `part<K>: <answer>` lines.
+fn parse_answers(answers: &str) -> Result<Vec<(u32, &str)>> {
+    answers
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| {
+            let (part, answer) = l
+                .split_once(':')
+                .ok_or_else(|| anyhow!("expected `part<K>: <answer>`, got {:?}", l))?;
+            let part = part
+                .trim()
+                .strip_prefix("part")
+                .and_then(|k| k.parse().ok())
+                .ok_or_else(|| anyhow!("no part number in {:?}", l))?;
+            Ok((part, answer.trim()))
+        })
+        .collect()
+}
+
+/// Checks one example file, returning the parts it covered.
+fn check(day: u32, example: &Path) -> Result<Vec<u32>> {
+    let input = read_to_string(example)?;
+    let answers =
+        read_to_string(example.with_extension("answers")).context("no .answers file next to it")?;
+    let answers = parse_answers(&answers)?;
+    if answers.is_empty() {
+        bail!("no answers to check");
+    }
+    for &(part, expected) in &answers {
+        let solve =
+            solver(day, part).ok_or_else(|| anyhow!("no solver for day {} part {}", day, part))?;
+        let answer = solve(&input).with_context(|| format!("part {}", part))?;
+        if answer != expected {
+            bail!("part {}: expected {}, got {}", part, expected, answer);
+        }
+    }
+    Ok(answers.iter().map(|&(part, _)| part).collect())
+}
+
+#[test]
+fn examples() -> Result<()> {
+    let mut covered = Vec::new();
+    let mut failures = Vec::new();
+    for day_dir in read_dir("examples/2020")? {
+        let day_dir = day_dir?.path();
+        let day = day_dir
+            .file_name()
+            .and_then(|n| n.to_str()?.strip_prefix("day")?.parse().ok())
+            .ok_or_else(|| anyhow!("{} isn't named day<N>", day_dir.display()))?;
+        for example in read_dir(&day_dir)? {
+            let example = example?.path();
+            match example.extension().and_then(|e| e.to_str()) {
+                Some("txt") => {}
+                Some("answers") if example.with_extension("txt").exists() => continue,
+                _ => {
+                    failures.push(format!("{}: not an example", example.display()));
+                    continue;
+                }
+            }
+            match check(day, &example) {
+                Ok(parts) => covered.extend(parts.into_iter().map(|part| (day, part))),
+                Err(e) => failures.push(format!("{}: {:#}", example.display(), e)),
+            }
+        }
+    }
+    for day in 1..=25 {
+        for part in 1..=2 {
+            let unchecked = !covered.contains(&(day, part)) && !TOO_SLOW.contains(&(day, part));
+            if solver(day, part).is_some() && unchecked {
+                failures.push(format!("day {} part {}: no example", day, part));
+            }
+        }
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+    Ok(())
+}
@@ -0,0 +1,175 @@
+//! Feeds arbitrary and mutated text to every `input_generator`, and to the parsers of the days in
+//! `legacy/`, which have to return either a parsed value or an error, never panic.
+//!
+//! Mutations start from the puzzle examples under `examples/2020`, so most cases get past the
+//! first few characters. Proptest's own seed files under `proptest-regressions/` only replay
+//! failures on the machine that found them; an input that crashed a parser goes into
+//! `regressions` below, which is what keeps it fixed. Set `PROPTEST_CASES` to fuzz longer.
+
+use std::fs::{read_dir, read_to_string};
+
+use proptest::{prelude::*, sample::Index};
+
+use crate::{day10, day11, day12, day13, day14, day15, day16, day18, day20, day25};
+
+/// Runs the day's generator or parser, dropping whatever it returns.
+const GENERATORS: [(u32, fn(&str)); 16] = [
+    (2, |input| drop(day02::parse_entries(input))),
+    (3, |input| drop(input.parse::<day03::Map>())),
+    (5, |input| {
+        for line in input.lines() {
+            drop(day05::BoardingPass::parse(line, day05::Layout::default()));
+        }
+    }),
+    (6, |input| drop(day06::parse_groups(input))),
+    (7, |input| drop(day07::BagGraph::parse(input))),
+    (8, |input| drop(day08::parse_program(input))),
+    (10, |input| drop(day10::input_generator(input))),
+    (11, |input| drop(day11::input_generator(input))),
+    (12, |input| drop(day12::input_generator(input))),
+    (13, |input| drop(day13::input_generator(input))),
+    (14, |input| drop(day14::input_generator(input))),
+    (15, |input| drop(day15::input_generator(input))),
+    (16, |input| drop(day16::input_generator(input))),
+    (18, |input| drop(day18::input_generator(input))),
+    (20, |input| drop(day20::input_generator(input))),
+    (25, |input| drop(day25::input_generator(input))),
+];
+
+fn generate_all(input: &str) {
+    for (_, generate) in GENERATORS.iter() {
+        generate(input);
+    }
+}
+
+/// Every example input.
+fn examples() -> Vec<String> {
+    let mut examples = Vec::new();
+    for day_dir in read_dir("examples/2020").expect("examples missing") {
+        for example in read_dir(day_dir.unwrap().path()).unwrap() {
+            let example = example.unwrap().path();
+            if example.extension().map_or(false, |e| e == "txt") {
+                examples.push(read_to_string(example).unwrap());
+            }
+        }
+    }
+    examples.sort();
+    examples
+}
+
+/// Characters the puzzle inputs are made of, so insertions look like almost valid input.
+const PUZZLE_TEXT: &str = "[0-9a-zA-Z .#,:=()+*\\[\\]\n-]{1,8}";
+
+#[derive(Debug, Clone)]
+enum Mutation {
+    Delete(Index, usize),
+    Insert(Index, String),
+    Replace(Index, char),
+    DuplicateLine(Index),
+}
+
+impl Mutation {
+    fn apply(&self, text: &mut Vec<char>) {
+        if text.is_empty() {
+            if let Mutation::Insert(_, s) = self {
+                text.extend(s.chars());
+            }
+            return;
+        }
+        match self {
+            Mutation::Delete(at, len) => {
+                let at = at.index(text.len());
+                let end = (at + len).min(text.len());
+                text.drain(at..end);
+            }
+            Mutation::Insert(at, s) => {
+                let at = at.index(text.len() + 1);
+                text.splice(at..at, s.chars());
+            }
+            Mutation::Replace(at, c) => {
+                let at = at.index(text.len());
+                text[at] = *c;
+            }
+            Mutation::DuplicateLine(at) => {
+                let at = at.index(text.len());
+                let start = text[..at]
+                    .iter()
+                    .rposition(|&c| c == '\n')
+                    .map_or(0, |i| i + 1);
+                let end = text[at..]
+                    .iter()
+                    .position(|&c| c == '\n')
+                    .map_or(text.len(), |i| at + i + 1);
+                let line = text[start..end].to_vec();
+                text.splice(start..start, line);
+            }
+        }
+    }
+}
+
+fn mutation() -> impl Strategy<Value = Mutation> {
+    prop_oneof![
+        (any::<Index>(), 1..20usize).prop_map(|(at, len)| Mutation::Delete(at, len)),
+        (any::<Index>(), PUZZLE_TEXT).prop_map(|(at, s)| Mutation::Insert(at, s)),
+        (any::<Index>(), any::<String>()).prop_map(|(at, s)| Mutation::Insert(at, s)),
+        (any::<Index>(), any::<char>()).prop_map(|(at, c)| Mutation::Replace(at, c)),
+        any::<Index>().prop_map(Mutation::DuplicateLine),
+    ]
+}
+
+/// An example with a few mutations applied.
+fn mutated_example() -> impl Strategy<Value = String> {
+    (
+        prop::sample::select(examples()),
+        prop::collection::vec(mutation(), 1..5),
+    )
+        .prop_map(|(example, mutations)| {
+            let mut text = example.chars().collect();
+            for m in &mutations {
+                m.apply(&mut text);
+            }
+            text.into_iter().collect()
+        })
+}
+
+proptest! {
+    #[test]
+    fn arbitrary_text(input in any::<String>()) {
+        generate_all(&input);
+    }
+
+    #[test]
+    fn puzzle_like_text(input in "[0-9a-zA-Z .#,:=()+*\\[\\]\n-]{0,200}") {
+        generate_all(&input);
+    }
+
+    #[test]
+    fn mutated_examples(input in mutated_example()) {
+        generate_all(&input);
+    }
+}
+
+/// Inputs that made a generator or parser panic.
+#[test]
+fn regressions() {
+    let regressions: &[(u32, &str)] = &[
+        (10, "1\n2\nthree\n"),
+        (10, "256\n"),
+        (11, ""),
+        (11, "\n"),
+        (11, "L.L\nLL\n"),
+        (11, "L.x\n"),
+        (13, ""),
+        (13, "939\n"),
+        (13, "939\n7,0,x\n"),
+        (13, "939\nx,x\n"),
+        (14, "mask = 1\n"),
+        (14, "mem[8] = 68719476736\n"),
+        (14, "mem[65536] = 11\n"),
+        (18, &"(".repeat(100_000)),
+    ];
+    for &(day, input) in regressions {
+        let (_, generate) = GENERATORS.iter().find(|(d, _)| *d == day).unwrap();
+        generate(input);
+    }
+}
@@ -0,0 +1,698 @@
+//! Seeded random puzzle inputs, written in the same text format as the real ones so they go
+//! through each day's `input_generator`, or for days 1 to 9 the parsers in `legacy/`.
+//!
+//! Useful for stress tests and for benches that sweep input sizes. The same seed always gives the
+//! same input.
+
+use std::fmt::Write;
+
+use itertools::Itertools;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, RngCore, SeedableRng};
+
+pub fn rng(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
+
+/// Day 1: `n` distinct expense entries, exactly one pair and one triple of them summing to 2020.
+///
+/// The other entries are all above 1010, so no two of them make 2020, and they skip every value
+/// that would complete a sum with the planted five. That leaves room for about 1000 entries.
+pub fn expense_report(rng: &mut impl Rng, n: usize) -> String {
+    fn sums(entries: &[u32], k: usize) -> usize {
+        entries
+            .iter()
+            .combinations(k)
+            .filter(|tuple| tuple.iter().copied().sum::<u32>() == 2020)
+            .count()
+    }
+    assert!(n >= 5, "the pair and the triple take 5 entries");
+    let planted = loop {
+        let a = rng.gen_range(1..1010);
+        let x = rng.gen_range(1..1010);
+        let y = rng.gen_range(1..2020 - x);
+        let planted = vec![a, 2020 - a, x, y, 2020 - x - y];
+        if planted.iter().all_unique() && sums(&planted, 2) == 1 && sums(&planted, 3) == 1 {
+            break planted;
+        }
+    };
+    let completing = planted
+        .iter()
+        .map(|p| 2020 - p)
+        .chain(
+            planted
+                .iter()
+                .tuple_combinations()
+                .filter_map(|(p, q)| 2020u32.checked_sub(p + q)),
+        )
+        .collect::<Vec<_>>();
+    let others = (1011..=2020)
+        .filter(|e| !planted.contains(e) && !completing.contains(e))
+        .collect::<Vec<_>>();
+    assert!(n - 5 <= others.len(), "{} entries don't fit", n);
+    let mut entries = others
+        .choose_multiple(rng, n - 5)
+        .chain(&planted)
+        .copied()
+        .collect::<Vec<_>>();
+    entries.shuffle(rng);
+    entries.iter().map(|e| format!("{}\n", e)).collect()
+}
+
+/// Day 2: `n` password entries, letters and passwords drawn from `a` to `e` so both policies
+/// pass some of them.
+pub fn password_database(rng: &mut impl Rng, n: usize) -> String {
+    (0..n)
+        .map(|_| {
+            let first = rng.gen_range(1..=10);
+            let second = rng.gen_range(first + 1..=first + 10);
+            let password = (0..rng.gen_range(1..=20))
+                .map(|_| rng.gen_range(b'a'..=b'e') as char)
+                .collect::<String>();
+            format!(
+                "{}-{} {}: {}\n",
+                first,
+                second,
+                rng.gen_range(b'a'..=b'e') as char,
+                password
+            )
+        })
+        .collect()
+}
+
+/// Day 3: a `width` by `height` map, `trees` being the chance of a square holding a tree.
+pub fn tree_map(rng: &mut impl Rng, width: usize, height: usize, trees: f64) -> String {
+    let mut map = String::with_capacity((width + 1) * height);
+    for _ in 0..height {
+        map.extend((0..width).map(|_| if rng.gen_bool(trees) { '#' } else { '.' }));
+        map.push('\n');
+    }
+    map
+}
+
+/// Day 4: `n` passports in random field order, spread over one or more lines, `invalid` being the
+/// chance of one missing a required field or breaking its rule.
+pub fn passports(rng: &mut impl Rng, n: usize, invalid: f64) -> String {
+    let passport = |rng: &mut dyn RngCore| {
+        let mut fields = vec![
+            ("byr", rng.gen_range(1920..=2002).to_string()),
+            ("iyr", rng.gen_range(2010..=2020).to_string()),
+            ("eyr", rng.gen_range(2020..=2030).to_string()),
+            (
+                "hgt",
+                if rng.gen_bool(0.5) {
+                    format!("{}cm", rng.gen_range(150..=193))
+                } else {
+                    format!("{}in", rng.gen_range(59..=76))
+                },
+            ),
+            ("hcl", format!("#{:06x}", rng.gen_range(0..1 << 24))),
+            (
+                "ecl",
+                ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"][rng.gen_range(0..7)].to_string(),
+            ),
+            ("pid", format!("{:09}", rng.gen_range(0..1_000_000_000))),
+        ];
+        if rng.gen_bool(invalid) {
+            let broken = rng.gen_range(0..fields.len());
+            if rng.gen_bool(0.5) {
+                fields.remove(broken);
+            } else {
+                fields[broken].1 = match fields[broken].0 {
+                    "byr" => "2003",
+                    "iyr" => "2009",
+                    "eyr" => "2031",
+                    "hgt" => "194",
+                    "hcl" => "123abc",
+                    "ecl" => "wat",
+                    _ => "01234567",
+                }
+                .to_string();
+            }
+        }
+        if rng.gen_bool(0.5) {
+            fields.push(("cid", rng.gen_range(100..350).to_string()));
+        }
+        fields.shuffle(rng);
+        let mut text = String::new();
+        for (i, (key, value)) in fields.iter().enumerate() {
+            if i > 0 {
+                text.push(if rng.gen_bool(0.3) { '\n' } else { ' ' });
+            }
+            write!(text, "{}:{}", key, value).unwrap();
+        }
+        text
+    };
+    (0..n)
+        .map(|_| format!("{}\n", passport(rng)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Day 5: `n` shuffled boarding passes for the default 128 by 8 plane, taking a run of seat IDs
+/// but for one seat inside it, which is at most 1021 passes.
+pub fn boarding_passes(rng: &mut impl Rng, n: usize) -> String {
+    assert!(
+        (2..=1021).contains(&n),
+        "{} passes don't leave a free seat between taken ones",
+        n
+    );
+    let first = rng.gen_range(0..1024 - n);
+    let free = first + rng.gen_range(1..n);
+    let mut ids = (first..=first + n)
+        .filter(|&id| id != free)
+        .collect::<Vec<_>>();
+    ids.shuffle(rng);
+    ids.iter()
+        .map(|id| {
+            let mut pass = (0..10)
+                .rev()
+                .map(|bit| match (bit >= 3, id >> bit & 1 == 1) {
+                    (true, false) => 'F',
+                    (true, true) => 'B',
+                    (false, false) => 'L',
+                    (false, true) => 'R',
+                })
+                .collect::<String>();
+            pass.push('\n');
+            pass
+        })
+        .collect()
+}
+
+/// Day 6: `groups` answer groups of 1 to 5 people, who mostly agree on the questions their group
+/// likes.
+pub fn answer_groups(rng: &mut impl Rng, groups: usize) -> String {
+    (0..groups)
+        .map(|_| {
+            let liked = (0..26).map(|_| rng.gen_bool(0.3)).collect::<Vec<_>>();
+            let mut group = String::new();
+            for _ in 0..rng.gen_range(1..=5) {
+                let person = loop {
+                    let person = (b'a'..=b'z')
+                        .zip(&liked)
+                        .filter(|(_, &liked)| rng.gen_bool(if liked { 0.9 } else { 0.05 }))
+                        .map(|(q, _)| q as char)
+                        .collect::<String>();
+                    if !person.is_empty() {
+                        break person;
+                    }
+                };
+                writeln!(group, "{}", person).unwrap();
+            }
+            group
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Day 7: rules for `n` distinct bags, shiny gold among them, each holding up to 3 kinds of the
+/// bags after it in a random order, so they never contain themselves.
+///
+/// Deep chains multiply fast, so for larger `n` counting what's inside a bag can overflow.
+pub fn bag_rules(rng: &mut impl Rng, n: usize) -> String {
+    const MODIFIERS: [&str; 16] = [
+        "bright", "clear", "dark", "dim", "dotted", "drab", "dull", "faded", "light", "mirrored",
+        "muted", "pale", "plaid", "posh", "shiny", "wavy",
+    ];
+    const COLORS: [&str; 16] = [
+        "aqua", "beige", "black", "blue", "bronze", "coral", "cyan", "gold", "green", "lime",
+        "olive", "plum", "red", "tan", "teal", "white",
+    ];
+    assert!(
+        n <= MODIFIERS.len() * COLORS.len(),
+        "only {} bags have names",
+        MODIFIERS.len() * COLORS.len()
+    );
+    let names = MODIFIERS
+        .iter()
+        .cartesian_product(&COLORS)
+        .map(|(modifier, color)| format!("{} {}", modifier, color))
+        .collect::<Vec<_>>();
+    let mut bags = names.choose_multiple(rng, n).collect::<Vec<_>>();
+    if !bags.iter().any(|bag| *bag == "shiny gold") && n > 0 {
+        bags[rng.gen_range(0..n)] = names.iter().find(|bag| *bag == "shiny gold").unwrap();
+    }
+    let mut rules = bags
+        .iter()
+        .enumerate()
+        .map(|(i, bag)| {
+            let kinds = rng.gen_range(0..=3);
+            let inside = bags[i + 1..]
+                .choose_multiple(rng, kinds)
+                .map(|inner| {
+                    let count = rng.gen_range(1..=4);
+                    format!(
+                        "{} {} bag{}",
+                        count,
+                        inner,
+                        if count == 1 { "" } else { "s" }
+                    )
+                })
+                .collect::<Vec<_>>();
+            if inside.is_empty() {
+                format!("{} bags contain no other bags.\n", bag)
+            } else {
+                format!("{} bags contain {}.\n", bag, inside.join(", "))
+            }
+        })
+        .collect::<Vec<_>>();
+    rules.shuffle(rng);
+    rules.concat()
+}
+
+/// Day 8: a boot program of `n` instructions that loops because of a single `jmp`, which swapped
+/// for a `nop` lets it run off the end.
+///
+/// The run up to that `jmp` only ever jumps forward, and swapping anything else on it lands back
+/// in that run, so no other swap repairs the program.
+pub fn boot_code(rng: &mut impl Rng, n: usize) -> String {
+    assert!(n >= 1, "the looping jmp needs a place");
+    let looping = rng.gen_range(0..n) as i32;
+    let n = n as i32;
+    (0..n)
+        .map(|pc| {
+            let (operation, argument) = if pc == looping {
+                ("jmp", -rng.gen_range(0..=pc))
+            } else {
+                match rng.gen_range(0..4) {
+                    0 | 1 => ("acc", rng.gen_range(-50..=50)),
+                    // as a `jmp`, a `nop` before the loop lands somewhere before it too
+                    2 if pc < looping => ("nop", rng.gen_range(-pc..=looping - pc)),
+                    2 => ("nop", rng.gen_range(-50..=50)),
+                    _ if pc < looping => ("jmp", rng.gen_range(1..=looping - pc)),
+                    _ => ("jmp", rng.gen_range(1..=n - pc)),
+                }
+            };
+            format!("{} {:+}\n", operation, argument)
+        })
+        .collect()
+}
+
+/// Day 9: `n` numbers where each after the first `preamble` is the sum of two different ones
+/// among the `preamble` before it, but for exactly one, which is the sum of a contiguous run
+/// instead.
+///
+/// The preamble is distinct numbers up to 100, so `preamble` is at most 100. Numbers grow by a
+/// few percent each, so thousands of them can overflow a `u64`.
+pub fn xmas_numbers(rng: &mut impl Rng, preamble: usize, n: usize) -> String {
+    fn is_sum(window: &[u64], n: u64) -> bool {
+        window
+            .iter()
+            .tuple_combinations()
+            .any(|(a, b)| a != b && a + b == n)
+    }
+    assert!(
+        (2..=100).contains(&preamble) && preamble + 1 < n,
+        "{} numbers can't follow a preamble of {}",
+        n,
+        preamble
+    );
+    let mut numbers = (1..=100u64)
+        .collect::<Vec<_>>()
+        .choose_multiple(rng, preamble)
+        .copied()
+        .collect::<Vec<_>>();
+    // from there on, the sum of everything so far is too big for two numbers in the window
+    let invalid = rng.gen_range(preamble + 1..n);
+    for i in preamble..n {
+        let window = &numbers[i - preamble..];
+        let next = loop {
+            let next = if i == invalid {
+                let start = rng.gen_range(0..i - 1);
+                numbers[start..rng.gen_range(start + 2..=i)].iter().sum()
+            } else {
+                let (a, b) = (window.choose(rng).unwrap(), window.choose(rng).unwrap());
+                if a == b {
+                    continue;
+                }
+                a + b
+            };
+            if (i == invalid) != is_sum(window, next) {
+                break next;
+            }
+        };
+        numbers.push(next);
+    }
+    numbers.iter().map(|n| format!("{}\n", n)).collect()
+}
+
+/// Day 10: `n` distinct adapter joltages, shuffled, consecutive ones 1 to 3 jolts apart.
+///
+/// Joltages have to fit a `u8`, so `n` is at most 255.
+pub fn adapters(rng: &mut impl Rng, n: usize) -> String {
+    assert!(n <= 255, "{} adapters don't fit in a u8", n);
+    let mut joltage = 0;
+    let mut adapters = (0..n)
+        .map(|i| {
+            // leave room for the adapters still to come
+            let room = 255 - joltage - (n - i - 1);
+            joltage += rng.gen_range(1..=3).min(room);
+            joltage
+        })
+        .collect::<Vec<_>>();
+    adapters.shuffle(rng);
+    adapters.iter().map(|j| format!("{}\n", j)).collect()
+}
+
+/// Day 11: a `width` by `height` seat layout, `floor` being the chance of a place being floor.
+pub fn seat_map(rng: &mut impl Rng, width: usize, height: usize, floor: f64) -> String {
+    let mut map = String::with_capacity((width + 1) * height);
+    for _ in 0..height {
+        map.extend((0..width).map(|_| if rng.gen_bool(floor) { '.' } else { 'L' }));
+        map.push('\n');
+    }
+    map
+}
+
+/// Day 11: a `seat_map` that settles under both rule sets, drawing new ones until it does.
+///
+/// Random layouts can flip between states forever, which the parts would never return from.
+pub fn settling_seat_map(rng: &mut impl Rng, width: usize, height: usize, floor: f64) -> String {
+    loop {
+        let map = seat_map(rng, width, height, floor);
+        if crate::day11::input_generator(&map)
+            .unwrap()
+            .settles(width * height)
+        {
+            return map;
+        }
+    }
+}
+
+/// Day 12: `n` navigation instructions, turns always by multiples of 90 degrees.
+pub fn navigation(rng: &mut impl Rng, n: usize) -> String {
+    (0..n)
+        .map(|_| match rng.gen_range(0..7) {
+            0..=4 => format!(
+                "{}{}\n",
+                b"NSEWF"[rng.gen_range(0..5)] as char,
+                rng.gen_range(1..=100)
+            ),
+            turn => format!(
+                "{}{}\n",
+                if turn == 5 { 'L' } else { 'R' },
+                90 * rng.gen_range(1..=3)
+            ),
+        })
+        .collect()
+}
+
+pub(crate) const PRIMES: [u32; 20] = [
+    7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83,
+];
+
+/// At most this many buses, so part 2's answer, below the product of their IDs, fits a `u64`
+/// even for the largest primes.
+pub const MAX_BUSES: usize = 10;
+
+/// Day 13: an earliest departure and `buses` distinct prime bus IDs, spread over a list with `x`
+/// for buses out of service.
+///
+/// `buses` is at most `MAX_BUSES`.
+pub fn bus_schedule(rng: &mut impl Rng, buses: usize) -> String {
+    assert!(
+        (1..=MAX_BUSES).contains(&buses),
+        "between 1 and {} buses, more can make the part 2 timestamp overflow a u64",
+        MAX_BUSES
+    );
+    let mut ids = PRIMES
+        .choose_multiple(rng, buses)
+        .copied()
+        .collect::<Vec<_>>();
+    // part 2 steps through time in multiples of the first bus
+    ids.sort_unstable_by(|a, b| b.cmp(a));
+    let mut list = Vec::new();
+    for id in ids {
+        list.extend((0..rng.gen_range(0..3)).map(|_| "x".to_string()));
+        list.push(id.to_string());
+    }
+    let first = list.iter().position(|s| s != "x").unwrap();
+    list.rotate_left(first);
+    format!("{}\n{}\n", rng.gen_range(0..1_000_000), list.join(","))
+}
+
+/// Day 14: `n` instructions, starting with a mask and changing it now and then.
+pub fn bitmask_program(rng: &mut impl Rng, n: usize) -> String {
+    let mut program = String::new();
+    for i in 0..n {
+        if i == 0 || rng.gen_bool(0.2) {
+            let mask = (0..36)
+                .map(|_| b"XXX01"[rng.gen_range(0..5)] as char)
+                .collect::<String>();
+            writeln!(program, "mask = {}", mask).unwrap();
+        } else {
+            let address = rng.gen_range(0..u16::MAX);
+            writeln!(
+                program,
+                "mem[{}] = {}",
+                address,
+                rng.gen_range(0..1u64 << 36)
+            )
+            .unwrap();
+        }
+    }
+    program
+}
+
+/// Day 15: `n` distinct starting numbers, at most 256 of them.
+pub fn starting_numbers(rng: &mut impl Rng, n: usize) -> String {
+    let numbers = (0..=255u8).collect::<Vec<_>>();
+    let numbers = numbers
+        .choose_multiple(rng, n)
+        .map(u8::to_string)
+        .collect::<Vec<_>>();
+    format!("{}\n", numbers.join(","))
+}
+
+/// Day 16: rules for `fields` fields, your ticket, and `tickets` nearby tickets.
+///
+/// Every column is assigned to a field and mostly holds values valid for it, a few nearby tickets
+/// carrying a value no rule allows.
+pub fn ticket_scan(rng: &mut impl Rng, fields: usize, tickets: usize) -> String {
+    assert!(fields >= 1, "a scan needs a field");
+    assert!(tickets >= 1, "a scan needs a nearby ticket");
+    let rules = (0..fields)
+        .map(|_| {
+            let low = rng.gen_range(1..400);
+            let gap = rng.gen_range(low + 1..600);
+            let high = rng.gen_range(gap + 1..990);
+            [(low, gap - 1), (gap + 1, high)]
+        })
+        .collect::<Vec<_>>();
+    let mut columns = (0..fields).collect::<Vec<_>>();
+    columns.shuffle(rng);
+
+    let ticket = |rng: &mut dyn RngCore, invalid: bool| {
+        let mut values = columns
+            .iter()
+            .map(|&field| {
+                let (from, to) = rules[field][rng.gen_range(0..2)];
+                rng.gen_range(from..=to)
+            })
+            .collect::<Vec<_>>();
+        if invalid {
+            // above every rule
+            values[rng.gen_range(0..fields)] = rng.gen_range(990..1000);
+        }
+        values
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+
+    let mut scan = String::new();
+    for (i, [(a, b), (c, d)]) in rules.iter().enumerate() {
+        writeln!(scan, "field {}: {}-{} or {}-{}", i, a, b, c, d).unwrap();
+    }
+    writeln!(scan, "\nyour ticket:\n{}", ticket(rng, false)).unwrap();
+    scan.push_str("\nnearby tickets:\n");
+    for _ in 0..tickets {
+        let invalid = rng.gen_bool(0.25);
+        writeln!(scan, "{}", ticket(rng, invalid)).unwrap();
+    }
+    scan
+}
+
+/// Day 18: `n` expressions of `terms` single digits each, nested in random parentheses.
+///
+/// Every digit might be a 9 multiplied into the result, so with more than 16 terms a line could
+/// overflow a `u64`.
+pub fn homework(rng: &mut impl Rng, n: usize, terms: usize) -> String {
+    fn expression(rng: &mut impl Rng, terms: usize) -> String {
+        let mut text = String::new();
+        let mut left = terms;
+        while left > 0 {
+            if !text.is_empty() {
+                text.push_str(if rng.gen_bool(0.5) { " + " } else { " * " });
+            }
+            // parentheses need at least two terms inside, and the whole line can't be one group
+            let group = if left >= 2 && left < terms && rng.gen_bool(0.3) {
+                rng.gen_range(2..=left)
+            } else {
+                1
+            };
+            if group == 1 {
+                write!(text, "{}", rng.gen_range(1..=9)).unwrap();
+            } else {
+                write!(text, "({})", expression(rng, group)).unwrap();
+            }
+            left -= group;
+        }
+        text
+    }
+    assert!(terms >= 2, "an expression needs at least two terms");
+    (0..n)
+        .map(|_| format!("{}\n", expression(rng, terms)))
+        .collect()
+}
+
+/// Day 20: `n` tiles with distinct four digit IDs and random 10 by 10 images.
+pub fn image_tiles(rng: &mut impl Rng, n: usize) -> String {
+    let ids = (1000..10000u16).collect::<Vec<_>>();
+    ids.choose_multiple(rng, n)
+        .map(|id| {
+            let mut tile = format!("Tile {}:\n", id);
+            for _ in 0..10 {
+                tile.extend((0..10).map(|_| if rng.gen_bool(0.5) { '#' } else { '.' }));
+                tile.push('\n');
+            }
+            tile
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Day 25: the card's and the door's public key, for loop sizes below `max_loop_size`.
+pub fn public_keys(rng: &mut impl Rng, max_loop_size: u64) -> String {
+    let card = crate::day25::public_key(rng.gen_range(1..max_loop_size));
+    let door = crate::day25::public_key(rng.gen_range(1..max_loop_size));
+    format!("{}\n{}\n", card, door)
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::{day10, day11, day12, day13, day14, day15, day16, day18, day20, day25};
+
+    #[test]
+    fn seeded() {
+        assert_eq!(
+            seat_map(&mut rng(7), 20, 5, 0.3),
+            seat_map(&mut rng(7), 20, 5, 0.3)
+        );
+        assert_ne!(
+            seat_map(&mut rng(7), 20, 5, 0.3),
+            seat_map(&mut rng(8), 20, 5, 0.3)
+        );
+    }
+
+    #[test]
+    fn inputs_parse() {
+        for seed in 0..20 {
+            let rng = &mut rng(seed);
+
+            let entries = expense_report(rng, 200)
+                .lines()
+                .map(|l| l.parse().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(200, entries.len());
+            assert_eq!(1, day01::k_sum(&entries, 2, 2020).unwrap().len());
+            assert_eq!(1, day01::k_sum(&entries, 3, 2020).unwrap().len());
+
+            let entries = day02::parse_entries(&password_database(rng, 100)).unwrap();
+            let report = day02::report(&entries, &[&day02::CountInRange, &day02::XorPosition]);
+            assert!(report.valid.iter().all(|(_, valid)| *valid > 0));
+
+            let map = tree_map(rng, 31, 50, 0.2).parse::<day03::Map>().unwrap();
+            map.trees_on_slope(3, 1);
+
+            let input = passports(rng, 30, 0.5);
+            let passports = records::records(&input)
+                .map(|lines| lines.join("\n"))
+                .collect::<Vec<_>>();
+            assert_eq!(30, passports.len());
+            let schema = day04::Schema::puzzle();
+            let valid = passports.iter().filter(|p| schema.is_valid(p)).count();
+            assert!(0 < valid && valid < 30);
+
+            let passes = boarding_passes(rng, 800)
+                .lines()
+                .map(|l| day05::BoardingPass::parse(l, day05::Layout::default()).unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(800, passes.len());
+            assert_eq!(1, day05::SeatMap::new(&passes).single_gaps().len());
+
+            assert_eq!(
+                25,
+                day06::parse_groups(&answer_groups(rng, 25)).unwrap().len()
+            );
+
+            let graph = day07::BagGraph::parse(&bag_rules(rng, 60)).unwrap();
+            graph
+                .containers_of(&day07::Bag::new("shiny", "gold"))
+                .unwrap();
+
+            let program = day08::parse_program(&boot_code(rng, 300)).unwrap();
+            day08::repair(&program).unwrap();
+
+            let numbers = xmas_numbers(rng, 25, 500)
+                .lines()
+                .map(|l| l.parse().unwrap())
+                .collect::<Vec<_>>();
+            let invalid = day09::invalid(numbers.iter().copied(), 25).collect::<Vec<_>>();
+            assert_eq!(1, invalid.len());
+            assert!(day09::contiguous_sum(&numbers, invalid[0].1).is_some());
+
+            let adapters = day10::input_generator(&adapters(rng, 255)).unwrap();
+            assert_eq!(255, adapters.iter().unique().count());
+            assert!(adapters.iter().tuple_windows().all(|(a, b)| b - a <= 3));
+            assert!(adapters[0] <= 3);
+
+            let area = day11::input_generator(&settling_seat_map(rng, 13, 7, 0.2)).unwrap();
+            day11::part1(&area);
+            day11::part2(&area).unwrap();
+
+            assert_eq!(
+                50,
+                day12::input_generator(&navigation(rng, 50)).unwrap().len()
+            );
+
+            let schedule = day13::input_generator(&bus_schedule(rng, 5)).unwrap();
+            day13::part1(&schedule);
+            let schedule = day13::input_generator(&bus_schedule(rng, MAX_BUSES)).unwrap();
+            day13::part2(&schedule).unwrap();
+
+            assert_eq!(
+                40,
+                day14::input_generator(&bitmask_program(rng, 40))
+                    .unwrap()
+                    .len()
+            );
+
+            assert_eq!(
+                12,
+                day15::input_generator(&starting_numbers(rng, 12))
+                    .unwrap()
+                    .len()
+            );
+
+            let scan = day16::input_generator(&ticket_scan(rng, 8, 30)).unwrap();
+            day16::part1(&scan);
+
+            let homework = day18::input_generator(&homework(rng, 10, 16)).unwrap();
+            assert_eq!(10, homework.len());
+            day18::part1(&homework);
+
+            assert_eq!(
+                6,
+                day20::input_generator(&image_tiles(rng, 6)).unwrap().len()
+            );
+
+            let keys = day25::input_generator(&public_keys(rng, 1000)).unwrap();
+            day25::part1(&keys).unwrap();
+        }
+    }
+}
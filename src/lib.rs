@@ -3,17 +3,25 @@ extern crate aoc_runner;
 extern crate aoc_runner_derive;
 use aoc_runner_derive::aoc_lib;
 
-mod day10;
-mod day11;
-mod day12;
+mod day05;
+pub mod day10;
+pub mod day11;
+pub mod day12;
 mod day13;
 mod day14;
-mod day15;
+pub mod day15;
 mod day16;
 // mod day17;
 mod day18;
 // mod day19;
 mod day20;
 mod day25;
+#[cfg(test)]
+mod examples;
+#[cfg(test)]
+mod fuzz;
+pub mod gen;
+pub mod parsing;
+pub mod reference;
 
 aoc_lib! { year = 2020 }
@@ -1,3 +1,114 @@
+//! Runs every solved day on its input.
+//!
+//! Logging is off unless asked for, and goes to stderr: `-v` logs each phase, `-vv` each step of
+//! it and `-vvv` everything. `--log day11=trace,day14=debug` picks days and levels instead.
+//! Legacy crates the runner goes through, see `LEGACY`, log the same way.
+
+use std::{env, io, process};
+
 use aoc_runner_derive::aoc_main;
+use tracing::Level;
+use tracing_subscriber::{filter::Targets, prelude::*};
+
+/// Legacy crates run as one of the days, which log under their own crate name.
+const LEGACY: [&str; 1] = ["day05"];
+
+const USAGE: &str = "usage: advent-of-code [-v | -vv | -vvv] [--log day<N>=<level>,...]";
+
+/// The log filter the arguments ask for, `None` for no logging.
+fn filter(mut args: impl Iterator<Item = String>) -> Result<Option<Targets>, String> {
+    let mut filter = None;
+    while let Some(arg) = args.next() {
+        let level = match arg.as_str() {
+            "-v" => Level::INFO,
+            "-vv" => Level::DEBUG,
+            "-vvv" => Level::TRACE,
+            "--log" => {
+                let days = args.next().ok_or("--log needs day<N>=<level>")?;
+                let mut targets = filter.unwrap_or_else(Targets::new);
+                for day in days.split(',') {
+                    let (day, level) = day
+                        .split_once('=')
+                        .ok_or_else(|| format!("expected day<N>=<level>, got {}", day))?;
+                    let level = level
+                        .parse::<Level>()
+                        .map_err(|_| format!("unknown level {}", level))?;
+                    targets = targets.with_target(format!("advent_of_code::{}", day), level);
+                    if LEGACY.contains(&day) {
+                        targets = targets.with_target(day, level);
+                    }
+                }
+                filter = Some(targets);
+                continue;
+            }
+            _ => return Err(format!("unknown argument {}", arg)),
+        };
+        filter = Some(
+            filter
+                .unwrap_or_else(Targets::new)
+                .with_target("advent_of_code", level)
+                .with_targets(LEGACY.iter().map(|&krate| (krate, level))),
+        );
+    }
+    Ok(filter)
+}
+
+/// Sends the events the arguments ask for to stderr, exiting with the usage on bad ones.
+fn init_logging() {
+    match filter(env::args().skip(1)) {
+        Ok(Some(filter)) => tracing_subscriber::registry()
+            .with(tracing_subscriber::fmt::layer().with_writer(io::stderr))
+            .with(filter)
+            .init(),
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            process::exit(2);
+        }
+    }
+}
+
+fn main() {
+    init_logging();
+
+    // `aoc_main!` expands to a `fn main` running every day, which only exists inside this block;
+    // this calls that one, not itself
+    aoc_main! { lib = advent_of_code }
+    main();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::filter;
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        args.iter()
+            .map(|a| a.to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn arguments() {
+        assert!(filter(args(&[])).unwrap().is_none());
+        let vv = filter(args(&["-vv"])).unwrap().unwrap();
+        assert!(vv.would_enable("advent_of_code::day11", &tracing::Level::DEBUG));
+        assert!(!vv.would_enable("advent_of_code::day11", &tracing::Level::TRACE));
+        assert!(vv.would_enable("day05", &tracing::Level::DEBUG));
+
+        let days = filter(args(&["--log", "day11=trace,day14=info"]))
+            .unwrap()
+            .unwrap();
+        assert!(days.would_enable("advent_of_code::day11", &tracing::Level::TRACE));
+        assert!(!days.would_enable("advent_of_code::day14", &tracing::Level::DEBUG));
+        assert!(!days.would_enable("advent_of_code::day12", &tracing::Level::ERROR));
+        assert!(!days.would_enable("day05", &tracing::Level::ERROR));
+        let legacy = filter(args(&["--log", "day05=trace"])).unwrap().unwrap();
+        assert!(legacy.would_enable("day05", &tracing::Level::TRACE));
 
-aoc_main! { lib = advent_of_code }
+        assert!(filter(args(&["-x"])).is_err());
+        assert!(filter(args(&["--log"])).is_err());
+        assert!(filter(args(&["--log", "day11"])).is_err());
+        assert!(filter(args(&["--log", "day11=loud"])).is_err());
+    }
+}
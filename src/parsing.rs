@@ -0,0 +1,238 @@
+//! nom combinators shared by the generators, and `ParseError`, which points at the line and
+//! column where parsing failed and says what was expected there.
+
+use std::{fmt, str::FromStr};
+
+use nom::{
+    character::complete::{char, digit1, line_ending, multispace0, one_of},
+    combinator::{all_consuming, cut, map_res, opt, recognize, verify},
+    error::{context, VerboseError, VerboseErrorKind},
+    multi::{many1, separated_list1},
+    sequence::{pair, terminated},
+    Finish, Offset, Parser,
+};
+
+pub type IResult<I, O> = nom::IResult<I, O, VerboseError<I>>;
+
+/// `42`
+pub fn unsigned<T: FromStr>(input: &str) -> IResult<&str, T> {
+    context("unsigned integer", map_res(digit1, str::parse))(input)
+}
+
+/// `42`, `+42`, `-42`
+pub fn signed<T: FromStr>(input: &str) -> IResult<&str, T> {
+    context(
+        "integer",
+        map_res(recognize(pair(opt(one_of("+-")), digit1)), str::parse),
+    )(input)
+}
+
+/// `a,b,c`, with an item after every comma.
+pub fn comma_list<'a, O>(
+    mut item: impl Parser<&'a str, O, VerboseError<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    move |input| {
+        let (mut input, first) = item.parse(input)?;
+        let mut items = vec![first];
+        while let Ok((rest, _)) = char::<_, VerboseError<&str>>(',')(input) {
+            let (rest, next) = cut(|i| item.parse(i))(rest)?;
+            items.push(next);
+            input = rest;
+        }
+        Ok((input, items))
+    }
+}
+
+/// Blocks separated by a blank line.
+pub fn blocks<'a, O>(
+    block: impl Parser<&'a str, O, VerboseError<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    separated_list1(pair(line_ending, line_ending), block)
+}
+
+/// Rows of one `cell` per character, all of the same width.
+pub fn grid<'a, O>(
+    cell: impl Parser<&'a str, O, VerboseError<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<Vec<O>>> {
+    context(
+        "rows of equal width",
+        verify(
+            separated_list1(line_ending, many1(cell)),
+            |rows: &Vec<Vec<O>>| rows.iter().all(|row| row.len() == rows[0].len()),
+        ),
+    )
+}
+
+/// Runs `parser` over the whole of `input`, allowing trailing whitespace.
+pub fn parse<'a, O>(
+    input: &'a str,
+    parser: impl Parser<&'a str, O, VerboseError<&'a str>>,
+) -> Result<O, ParseError> {
+    all_consuming(terminated(parser, multispace0))(input)
+        .finish()
+        .map(|(_rest, parsed)| parsed)
+        .map_err(|e| ParseError::new(input, e))
+}
+
+/// Runs `parser` over each line of `input`, failing on the first line it doesn't match entirely.
+pub fn lines<'a, O>(
+    input: &'a str,
+    mut parser: impl Parser<&'a str, O, VerboseError<&'a str>>,
+) -> Result<Vec<O>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            all_consuming(|l| parser.parse(l))(line)
+                .finish()
+                .map(|(_rest, parsed)| parsed)
+                .map_err(|e| ParseError {
+                    line: i + 1,
+                    ..ParseError::new(line, e)
+                })
+        })
+        .collect()
+}
+
+/// Where and why parsing failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// 1-based.
+    pub line: usize,
+    /// 1-based, in characters.
+    pub column: usize,
+    /// What was being parsed, outermost first.
+    pub context: Vec<&'static str>,
+    pub expected: String,
+    pub source_line: String,
+}
+
+impl ParseError {
+    fn new(input: &str, error: VerboseError<&str>) -> ParseError {
+        // nom lists the innermost failure first, and the contexts it was in after it
+        let (rest, expected) = match error
+            .errors
+            .iter()
+            .find(|(_, kind)| !matches!(kind, VerboseErrorKind::Context(_)))
+        {
+            Some((rest, VerboseErrorKind::Char(c))) => (*rest, format!("{:?}", c)),
+            Some((rest, VerboseErrorKind::Nom(kind))) => (*rest, kind.description().to_lowercase()),
+            _ => (input, "something else".to_string()),
+        };
+        let offset = input.offset(rest);
+        let consumed = &input[..offset];
+        let line_start = consumed.rfind('\n').map_or(0, |i| i + 1);
+        ParseError {
+            line: consumed.matches('\n').count() + 1,
+            column: consumed[line_start..].chars().count() + 1,
+            context: error
+                .errors
+                .iter()
+                .rev()
+                .filter_map(|(_, kind)| match kind {
+                    VerboseErrorKind::Context(c) => Some(*c),
+                    _ => None,
+                })
+                .collect(),
+            expected,
+            source_line: input[line_start..].lines().next().unwrap_or("").to_string(),
+        }
+    }
+
+    fn found(&self) -> String {
+        match self.source_line.chars().nth(self.column - 1) {
+            Some(c) => format!("{:?}", c),
+            None => "end of line".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: expected {}, found {}",
+            self.line,
+            self.column,
+            self.expected,
+            self.found()
+        )?;
+        if !self.context.is_empty() {
+            write!(f, " in {}", self.context.join(" > "))?;
+        }
+        write!(
+            f,
+            "\n{}\n{:>width$}",
+            self.source_line,
+            '^',
+            width = self.column
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use nom::{
+        bytes::complete::tag,
+        character::complete::{line_ending, one_of},
+        sequence::separated_pair,
+    };
+
+    use super::*;
+
+    #[test]
+    fn combinators() {
+        assert_eq!(Ok(42u8), parse("42\n", unsigned));
+        assert_eq!(Ok(-42i32), parse("-42", signed));
+        assert_eq!(Ok(42i32), parse("+42", signed));
+        assert_eq!(Ok(vec![1u32, 2, 3]), parse("1,2,3", comma_list(unsigned)));
+        assert_eq!(
+            Ok(vec![vec![1u32, 2], vec![3]]),
+            parse(
+                "1,2\n3\n",
+                separated_list1(line_ending, comma_list(unsigned))
+            )
+        );
+        assert_eq!(
+            Ok(vec![vec![1u8, 2], vec![3]]),
+            parse("1\n2\n\n3", blocks(separated_list1(line_ending, unsigned)))
+        );
+        assert_eq!(
+            Ok(vec![vec!['#', '.'], vec!['.', '#']]),
+            parse("#.\n.#\n", grid(one_of(".#")))
+        );
+        assert_eq!(Ok(vec![1u8, 2]), lines("1\n2\n", unsigned));
+    }
+
+    #[test]
+    fn positions() {
+        let err = parse(
+            "1,2\n3,x",
+            separated_pair(
+                comma_list(unsigned::<u8>),
+                line_ending,
+                comma_list(unsigned::<u8>),
+            ),
+        )
+        .unwrap_err();
+        assert_eq!((2, 3), (err.line, err.column));
+        assert_eq!(
+            "line 2, column 3: expected digit, found 'x' in unsigned integer\n3,x\n  ^",
+            err.to_string()
+        );
+
+        let err = lines("mem = 3\nmem = 256", |l| {
+            let (l, _) = tag("mem = ")(l)?;
+            unsigned::<u8>(l)
+        })
+        .unwrap_err();
+        assert_eq!((2, 7), (err.line, err.column));
+        assert_eq!(vec!["unsigned integer"], err.context);
+
+        let err = parse("#.\n#", grid(one_of(".#"))).unwrap_err();
+        assert_eq!((1, 1), (err.line, err.column));
+        assert_eq!(vec!["rows of equal width"], err.context);
+    }
+}
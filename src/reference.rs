@@ -0,0 +1,255 @@
+//! Slow but obviously correct solutions, kept to check the optimised ones against.
+//!
+//! The property tests below run both on random inputs and let proptest shrink any disagreement
+//! down to a minimal counterexample.
+
+pub mod day10 {
+    use std::collections::HashMap;
+
+    use itertools::Itertools;
+
+    /// The memoised recursion part 2 used before the single pass, caching the count for every
+    /// remaining chain.
+    pub fn memoised(input: &[u8]) -> u128 {
+        let all_inputs: Vec<u8> = [0].iter().chain(input.iter()).copied().collect_vec();
+        combinations(all_inputs[0], &all_inputs[1..], &mut HashMap::new())
+    }
+
+    fn combinations(current: u8, input: &[u8], cache: &mut HashMap<Vec<u8>, u128>) -> u128 {
+        if input.is_empty() {
+            return 1;
+        }
+
+        let mut subcombinations: u128 = 0;
+        for next in input.iter().take_while(|x| current + 3 >= **x) {
+            let rest = &input
+                .iter()
+                .skip_while(|x| *next >= **x)
+                .copied()
+                .collect_vec();
+            let cache_key = [*next].iter().chain(rest.iter()).copied().collect_vec();
+            subcombinations += if let Some(val) = cache.get(&cache_key) {
+                *val
+            } else {
+                let val = combinations(*next, rest, cache);
+                cache.insert(cache_key, val);
+                val
+            }
+        }
+        subcombinations
+    }
+
+    /// Tries every subset of adapters that still forms a chain, one by one.
+    pub fn arrangements(adapters: &[u8]) -> u128 {
+        fn count(current: u16, rest: &[u8]) -> u128 {
+            if rest.is_empty() {
+                return 1;
+            }
+            (0..rest.len())
+                .take_while(|&i| u16::from(rest[i]) <= current + 3)
+                .map(|i| count(u16::from(rest[i]), &rest[i + 1..]))
+                .sum()
+        }
+        count(0, adapters)
+    }
+}
+
+pub mod day13 {
+    use crate::day13::Schedule;
+
+    /// Checks every departure of the first bus until the rest follow it.
+    pub fn subsequent_departures(schedule: &Schedule) -> usize {
+        (0..)
+            .step_by(schedule.busses.first().unwrap().unwrap() as usize)
+            .find(|timestamp| {
+                schedule
+                    .busses
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, x)| x.map(|b| (i, b)))
+                    .all(|(i, b)| (timestamp + i) % b as usize == 0)
+            })
+            .unwrap()
+    }
+}
+
+pub mod day14 {
+    use std::collections::HashMap;
+
+    use crate::day14::{floating_mask, to_u64, Instr};
+
+    /// Writes every address a floating mask expands to.
+    pub fn floating_memory_sum(input: &[Instr]) -> u64 {
+        let mut memory = HashMap::new();
+        let (mut ones, mut floating) = (0, 0);
+        for instr in input {
+            match instr {
+                Instr::Mask(mask) => {
+                    let (o, f) = floating_mask(mask);
+                    ones = o;
+                    floating = f;
+                }
+                Instr::Mem(address, value) => {
+                    let base = (u64::from(*address) | ones) & !floating;
+                    // every subset of the floating bits
+                    let mut subset = 0u64;
+                    loop {
+                        memory.insert(base | subset, to_u64(value));
+                        subset = subset.wrapping_sub(floating) & floating;
+                        if subset == 0 {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        memory.values().sum()
+    }
+}
+
+pub mod day15 {
+    /// Remembers every number spoken and searches back for the last time it came up.
+    pub fn play_until(input: &[u8], stop_at: u32) -> u32 {
+        let mut spoken = input.iter().map(|&n| u32::from(n)).collect::<Vec<_>>();
+        while spoken.len() < stop_at as usize {
+            let (&last, before) = spoken.split_last().unwrap();
+            let next = match before.iter().rposition(|&n| n == last) {
+                Some(i) => (before.len() - i) as u32,
+                None => 0,
+            };
+            spoken.push(next);
+        }
+        spoken[stop_at as usize - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use num::integer::gcd;
+    use proptest::{prelude::*, sample::subsequence};
+
+    use super::{day10, day13, day14, day15};
+    use crate::{day10 as fast10, day13 as fast13, day14 as fast14, day15 as fast15, gen};
+
+    /// Sorted adapters, each 1 to 3 jolts above the one before.
+    fn adapters() -> impl Strategy<Value = Vec<u8>> {
+        prop::collection::vec(1u8..=3, 0..18).prop_map(|steps| {
+            steps
+                .iter()
+                .scan(0, |joltage, step| {
+                    *joltage += step;
+                    Some(*joltage)
+                })
+                .collect()
+        })
+    }
+
+    /// Up to `gen::MAX_BUSES` of the generator's primes, repeats allowed so some share a factor,
+    /// and up to 3 larger IDs so some overflow, each after up to two `x`.
+    fn bus_schedule() -> impl Strategy<Value = (Vec<(u64, u32)>, String)> {
+        let primes = prop::collection::vec(
+            prop::sample::select(gen::PRIMES.to_vec()),
+            1..=gen::MAX_BUSES,
+        );
+        let large = prop::collection::vec(1u32..1_000_000, 0..=3);
+        (primes, large)
+            .prop_map(|(primes, large)| [primes, large].concat())
+            .prop_shuffle()
+            .prop_flat_map(|buses| {
+                let gaps = prop::collection::vec(0..3usize, buses.len());
+                (Just(buses), gaps)
+            })
+            .prop_map(|(buses, gaps)| {
+                let mut list = Vec::new();
+                let mut ids = Vec::new();
+                for (i, (bus, gap)) in buses.into_iter().zip(gaps).enumerate() {
+                    // the first entry has to be a bus
+                    if i > 0 {
+                        list.extend(std::iter::repeat("x".to_string()).take(gap));
+                    }
+                    ids.push((list.len() as u64, bus));
+                    list.push(bus.to_string());
+                }
+                (ids, format!("0\n{}", list.join(",")))
+            })
+    }
+
+    /// Part 2 in `u128`, which fits the product of any `bus_schedule`, for IDs sharing no factor.
+    fn earliest(ids: &[(u64, u32)]) -> u128 {
+        let (mut timestamp, mut step) = (0u128, 1u128);
+        for &(i, bus) in ids {
+            let bus = u128::from(bus);
+            while (timestamp + u128::from(i)) % bus != 0 {
+                timestamp += step;
+            }
+            step *= bus;
+        }
+        timestamp
+    }
+
+    /// Masks with at most 6 floating bits, writes to small addresses so they collide.
+    fn bitmask_program() -> impl Strategy<Value = String> {
+        let mask = (
+            prop::collection::vec(prop::sample::select(vec!['0', '1']), 36),
+            subsequence((0..36).collect::<Vec<_>>(), 0..=6),
+        )
+            .prop_map(|(mut mask, floating)| {
+                for i in floating {
+                    mask[i] = 'X';
+                }
+                format!("mask = {}", mask.into_iter().collect::<String>())
+            });
+        let write = (0u16..64, 0u64..1000)
+            .prop_map(|(address, value)| format!("mem[{}] = {}", address, value));
+        let instrs = prop::collection::vec(prop_oneof![1 => mask.clone(), 4 => write], 0..12);
+        (mask, instrs).prop_map(|(first, rest)| {
+            std::iter::once(first)
+                .chain(rest)
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn day10_arrangements(adapters in adapters()) {
+            prop_assert_eq!(day10::arrangements(&adapters), fast10::part2(&adapters));
+            prop_assert_eq!(day10::memoised(&adapters), fast10::part2(&adapters));
+        }
+
+        #[test]
+        fn day13_subsequent_departures((ids, input) in bus_schedule()) {
+            let coprime = ids.iter().tuple_combinations().all(|(a, b)| gcd(a.1, b.1) == 1);
+            let schedule = fast13::input_generator(&input).unwrap();
+            if !coprime {
+                prop_assert!(fast13::part2(&schedule).is_err());
+                return Ok(());
+            }
+            let expected = earliest(&ids);
+            match fast13::part2(&schedule) {
+                Ok(timestamp) => prop_assert_eq!(expected, timestamp as u128),
+                Err(_) => prop_assert!(expected > u128::from(u64::MAX)),
+            }
+            // brute force only gets through small products
+            if ids.iter().map(|&(_, bus)| u128::from(bus)).product::<u128>() < 1_000_000 {
+                prop_assert_eq!(expected, day13::subsequent_departures(&schedule) as u128);
+            }
+        }
+
+        #[test]
+        fn day14_floating_memory(input in bitmask_program()) {
+            let program = fast14::input_generator(&input).unwrap();
+            prop_assert_eq!(day14::floating_memory_sum(&program) as usize, fast14::part2(&program).unwrap());
+        }
+
+        #[test]
+        fn day15_play_until(
+            input in prop::collection::hash_set(any::<u8>(), 1..6).prop_map(|s| s.into_iter().collect::<Vec<_>>()),
+            stop_at in 1u32..2000,
+        ) {
+            prop_assume!(stop_at as usize > input.len());
+            prop_assert_eq!(day15::play_until(&input, stop_at), fast15::play_until(&input, stop_at));
+        }
+    }
+}